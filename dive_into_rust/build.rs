@@ -0,0 +1,196 @@
+//!
+//! 为每一章生成例子的注册表`$OUT_DIR/chapNN_examples.rs`，由对应章节的`mod.rs`用`include!`引入。
+//!
+//! 标注了`#[cfg_attr(test, test)]`的顶层函数就是一个例子，它的第一行非空文档注释作为标题。
+//! 注册表放在章节模块内部，所以例子函数不需要是`pub`的。
+//!
+//! 另外，开启`nightly` feature并且用的是nightly工具链时，设置`cfg(nightly)`，例子改用依赖不稳定特性的原始写法；
+//! 在stable工具链上这个feature只给出一个警告，所以`cargo test --all-features`在stable上也能通过。
+//!
+//! 有些章节用到了旧版本的语法，比如不带`dyn`的trait对象，所以这里按行扫描，而不是完整地解析源文件：
+//! 例子都写在行首，文档注释和属性紧挨着`fn`，中间只能有别的属性、普通注释和空行。
+//!
+//! 每个例子还有一个`dive run`用的编号，取自函数名开头的数字，共用一个编号的例子依次加上`a`、`b`……后缀。
+//!
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Default)]
+struct Pending {
+    docs: Vec<String>,
+    test: bool,
+    should_panic: bool,
+}
+
+struct Example {
+    chapter: u32,
+    name: String,
+    title: String,
+    should_panic: bool,
+    /// `文件:行号`，报错时用
+    location: String,
+}
+
+/// `fn name(`，前面可以有`pub`或者`pub(crate)`
+fn function_name(line: &str) -> Option<&str> {
+    let rest = match line.strip_prefix("pub") {
+        Some(rest) if rest.starts_with('(') => &rest[rest.find(')')? + 1..],
+        Some(rest) => rest,
+        None => line,
+    };
+    let name = rest.trim_start().strip_prefix("fn ")?.split(['(', '<']).next()?.trim();
+    if name.is_empty() { None } else { Some(name) }
+}
+
+fn examples(path: &Path, source: &str, chapter: u32) -> Vec<Example> {
+    let mut examples = Vec::new();
+    let mut pending = Pending::default();
+
+    for (index, line) in source.lines().enumerate() {
+        if let Some(doc) = line.strip_prefix("///") {
+            pending.docs.push(doc.trim().to_owned());
+        } else if line == "#[cfg_attr(test, test)]" {
+            pending.test = true;
+        } else if line == "#[cfg_attr(test, should_panic)]" {
+            pending.should_panic = true;
+        } else if line.starts_with("#[") || line.trim_start().starts_with("//") {
+            // 其他属性和普通注释不影响文档注释和函数的对应关系
+        } else if pending.test && line.trim().is_empty() {
+            // 属性和函数之间的空行
+        } else {
+            if pending.test {
+                let location = format!("{}:{}", path.display(), index + 1);
+                let name = function_name(line).unwrap_or_else(|| {
+                    panic!("{}: expected a function after #[cfg_attr(test, test)], found `{}`", location, line)
+                });
+                examples.push(Example {
+                    chapter,
+                    name: name.to_owned(),
+                    title: pending.docs.iter().find(|doc| !doc.is_empty()).cloned().unwrap_or_default(),
+                    should_panic: pending.should_panic,
+                    location,
+                });
+            }
+            pending = Pending::default();
+        }
+    }
+
+    examples
+}
+
+/// 函数名开头的编号，比如`_28_08_01_deadlock`的`28_08_01`
+fn number(name: &str) -> &str {
+    let name = name.trim_start_matches('_');
+    let mut end = 0;
+    for part in name.split('_') {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            break;
+        }
+        end += part.len() + 1;
+    }
+    &name[..end.saturating_sub(1)]
+}
+
+/// `dive run`用的编号，一个编号只对应一个例子。书里有些例子共用一个编号，
+/// 这些例子按章节和出现的顺序加上`a`、`b`……后缀；没有编号的例子直接用函数名。
+fn assign_ids(examples: &[Example]) -> Vec<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for example in examples {
+        *counts.entry(number(&example.name)).or_default() += 1;
+    }
+
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    let mut owners: HashMap<String, &str> = HashMap::new();
+    let mut ids = Vec::new();
+    for example in examples {
+        let number = number(&example.name);
+        let id = match counts[number] {
+            _ if number.is_empty() => example.name.trim_start_matches('_').to_owned(),
+            1 => number.to_owned(),
+            shared => {
+                let seen = seen.entry(number).or_default();
+                assert!(shared <= 26, "{} examples share the number {}", shared, number);
+                *seen += 1;
+                format!("{}{}", number, (b'a' + *seen as u8 - 1) as char)
+            }
+        };
+        if let Some(owner) = owners.insert(id.clone(), &example.location) {
+            panic!("{}: example id `{}` is already used at {}", example.location, id, owner);
+        }
+        ids.push(id);
+    }
+
+    ids
+}
+
+fn registry(examples: &[(&Example, &String)]) -> String {
+    let mut registry = String::from("pub(crate) const EXAMPLES: &[crate::examples::Example] = &[\n");
+    for (example, id) in examples {
+        writeln!(
+            registry,
+            "    crate::examples::Example {{ chapter: {}, id: {:?}, name: {:?}, title: {:?}, should_panic: {}, run: {} }},",
+            example.chapter, id, example.name, example.title, example.should_panic, example.name,
+        ).unwrap();
+    }
+    registry.push_str("];\n");
+    registry
+}
+
+fn nightly_toolchain() -> bool {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    match Command::new(rustc).arg("-V").output() {
+        Ok(output) => {
+            let version = String::from_utf8_lossy(&output.stdout);
+            version.contains("nightly") || version.contains("-dev")
+        }
+        Err(_) => false,
+    }
+}
+
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(nightly)");
+    if env::var_os("CARGO_FEATURE_NIGHTLY").is_some() {
+        if nightly_toolchain() {
+            println!("cargo:rustc-cfg=nightly");
+        } else {
+            println!("cargo:warning=the `nightly` feature needs a nightly toolchain, building the stable examples instead");
+        }
+    }
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let src = Path::new(&manifest_dir).join("src");
+    println!("cargo:rerun-if-changed=src");
+
+    let mut chapters = Vec::new();
+    for entry in fs::read_dir(&src).unwrap() {
+        let path = entry.unwrap().path();
+        let chapter = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) if name.starts_with("chap") => name.to_owned(),
+            _ => continue,
+        };
+        let number: u32 = chapter["chap".len()..].parse().unwrap();
+        chapters.push((number, chapter, path.join("mod.rs")));
+    }
+    // 编号的后缀按章节顺序分配，不依赖目录的遍历顺序
+    chapters.sort();
+
+    let mut all = Vec::new();
+    for (number, _, path) in &chapters {
+        let source = fs::read_to_string(path).unwrap();
+        all.extend(examples(path.strip_prefix(&manifest_dir).unwrap_or(path), &source, *number));
+    }
+    let ids = assign_ids(&all);
+
+    for (number, chapter, _) in &chapters {
+        let examples: Vec<(&Example, &String)> = all.iter().zip(&ids)
+            .filter(|(example, _)| example.chapter == *number)
+            .collect();
+        fs::write(Path::new(&out_dir).join(format!("{}_examples.rs", chapter)), registry(&examples)).unwrap();
+    }
+}
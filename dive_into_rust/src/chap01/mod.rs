@@ -2,7 +2,7 @@
 /// 带`!`的都是标准库定义的宏，类似还有，
 /// `format!` `write!` `writeln!` 参考标准库`std::fmt` 模块说明
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _01_05_format() {
     println!("{}", 1);                              // 默认用法，打印Display
     println!("{:o}", 9);                            // 八进制
@@ -18,4 +18,6 @@ fn _01_05_format() {
 
 
     println!("{a} {b} {b}", a = "x", b = "y");      // 命名参数
-}
\ No newline at end of file
+}
+
+include!(concat!(env!("OUT_DIR"), "/chap01_examples.rs"));
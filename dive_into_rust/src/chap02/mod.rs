@@ -7,13 +7,13 @@ mod adt {
 ///
 /// 变量必须先声明，后使用。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _02_01_variable_declaration() {
     let _variable: i32 = 100;
 }
 
 /// `let` 关键字，既是声明语句，也是 **模式解构(pattern destructure)**
-#[test]
+#[cfg_attr(test, test)]
 fn _02_03_pattern_destructure() {
     // `mut x`被视作一个组合
     let mut _x = 5;
@@ -39,7 +39,7 @@ fn _02_03_pattern_destructure() {
 }
 
 /// 类型没有“默认构造函数”，变量没有“默认值”。
-#[test]
+#[cfg_attr(test, test)]
 fn _02_03_initialization() {
     use rand::Rng;
     let mut rng = rand::thread_rng();
@@ -57,7 +57,7 @@ fn _02_03_initialization() {
 }
 
 /// 占位符表示忽略这个变量绑定，后面不再用到
-#[test]
+#[cfg_attr(test, test)]
 fn _02_03_placeholder() {
     let _ = "hello";
     // println!("{}", _);
@@ -73,7 +73,7 @@ fn _02_03_placeholder() {
 }
 
 /// 变量遮蔽
-#[test]
+#[cfg_attr(test, test)]
 fn _02_01_01_variable_shadowing() {
     let x = "hello";
     println!("x is {}", x);
@@ -103,7 +103,7 @@ fn _02_01_01_variable_shadowing() {
 
 
 /// 类型推导
-#[test]
+#[cfg_attr(test, test)]
 fn _02_01_02_type_infer() {
     // 没有明确标出变量的类型，但是通过字面量的后缀，
     // 编译器知道elem的类型为u8
@@ -154,7 +154,7 @@ fn _02_01_02_type_infer() {
 }
 
 /// 类型别名，主要作用是为了简化代码
-#[test]
+#[cfg_attr(test, test)]
 fn _02_01_03_type_alias() {
     type Age = u32;
 
@@ -171,7 +171,7 @@ fn _02_01_03_type_alias() {
 }
 
 /// 静态变量
-#[test]
+#[cfg_attr(test, test)]
 fn _02_01_04_static_variable() {
     static _GLOBAL: i32 = 0;
     // 与`let`语句一样，static语句同样也是一个模式匹配。与let不同的是，
@@ -220,7 +220,7 @@ fn _02_01_04_static_variable() {
 }
 
 /// 常量
-#[test]
+#[cfg_attr(test, test)]
 fn _02_01_05_constant() {
     // const 声明的是常量，而不是变量，因此一定不允许使用mut关键字修饰这个变量绑定
     // 它与static变量的最大区别在于：编译器并不一定会给const常量分配内存空间，在编译过程中，
@@ -230,7 +230,7 @@ fn _02_01_05_constant() {
 }
 
 /// bool
-#[test]
+#[cfg_attr(test, test)]
 fn _02_02_01_bool() {
 
     let x = true;
@@ -264,7 +264,7 @@ fn _02_02_01_bool() {
 }
 
 /// 字符类型
-#[test]
+#[cfg_attr(test, test)]
 fn _02_02_02_char() {
     let love = '❤';         // 可以直接嵌入任何 unicode 字符
     let c1 = '\n';          // 换行符
@@ -300,7 +300,7 @@ fn _02_02_02_char() {
 /// Pointer size    isize       usize
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _02_02_03_integer() {
 
     // 数字类型的字面量表示可以有许多方式
@@ -324,7 +324,7 @@ fn _02_02_03_integer() {
 
 
 /// 整数溢出
-#[test]
+#[cfg_attr(test, test)]
 fn _02_02_04_integer_overflow() {
     // 在C语言中，对于无符号类型，算术运算永远不会overflow，如果超过表示范围
     // 则自动舍弃高位数据。对于有符号类型，如果发生了overflow，标准规定这是undefined behavior,
@@ -373,7 +373,7 @@ fn _02_02_04_integer_overflow() {
 }
 
 /// 浮点类型
-#[test]
+#[cfg_attr(test, test)]
 fn _02_02_05_float() {
     // Rust提供了基于IEEE 754-2008标准的浮点类型
     // 按占据空间大小区分，分别为f32和f64
@@ -426,7 +426,7 @@ fn _02_02_05_float() {
 }
 
 /// 指针类型
-#[test]
+#[cfg_attr(test, test)]
 fn _02_02_06_pointer() {
     // 无GC的编程语言，如C、C++以及Rust，对数据的组织操作有更多的自由度，具体表现为：
 
@@ -463,7 +463,7 @@ fn _02_02_06_pointer() {
 
 // 更复杂的类型转换，一般使用标准库的From Into等trait
 /// 类型转换
-#[test]
+#[cfg_attr(test, test)]
 fn _02_02_07_type_transform() {
     let var1: i8 = 41;
     let var2: i16 = var1 as i16;    // Rust 希望可以显式标记类型转换，以防止隐藏的bug
@@ -478,7 +478,7 @@ fn _02_02_07_type_transform() {
 
 /// 元组类型
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _02_03_01_tuple() {
     let a = (1i32, false);          // 元组中包含两个元素，第一个是i32类型，第二个是bool类型
     let b = ("a", (1i32, 2i32));    // 元组中包含两个元素，第二个元素本身也是元组
@@ -507,7 +507,7 @@ fn _02_03_01_tuple() {
 }
 
 /// 结构体
-#[test]
+#[cfg_attr(test, test)]
 fn _02_03_02_struct() {
     // 结构体和元组类似，但用下标+字段访问
     struct Point {
@@ -556,9 +556,46 @@ fn _02_03_02_struct() {
     struct Foo3{}
 }
 
+/// 上面手写的`default()`函数可以交给`#[derive(SmartDefault)]`生成：
+/// 字段上的`#[default = 字面量]`或者`#[default(表达式)]`给出默认值，没有标注的字段使用它自己的`Default::default()`。
+/// 同时还会为每个字段生成`with_字段名`方法，效果和`..default()`的结构体更新语法类似。
+///
+/// 它没有叫`Default`，免得遮住标准库的`#[derive(Default)]`，后者可以用`#[default]`指定枚举的默认成员。
+#[cfg_attr(test, test)]
+fn _02_03_02_struct_default() {
+    use dive_into_rust_derive::SmartDefault;
+
+    #[derive(SmartDefault, Debug, PartialEq)]
+    struct Point3d {
+        #[default = 5]
+        x: i32,
+        y: i32,
+        #[default(-1)]
+        z: i32,
+        #[default = "origin"]
+        name: String,
+    }
+
+    #[derive(Default, Debug, PartialEq)]
+    enum Axis {
+        #[default]
+        X,
+        Y,
+        Z,
+    }
+
+    let origin = Point3d::default();
+    assert_eq!(origin, Point3d { x: 5, y: 0, z: -1, name: "origin".to_owned() });
+
+    let point = Point3d { z: 1, x: 2, ..Point3d::default() };
+    assert_eq!(point, Point3d::default().with_z(1).with_x(2));
+    assert_eq!(Point3d::default().with_name("p").name, "p");
+    assert_eq!(Axis::default(), Axis::X);
+}
+
 /// 元组-结构体
 /// tuple-struct
-#[test]
+#[cfg_attr(test, test)]
 fn _02_03_03_tuple_struct() {
     struct Color(i32, i32, i32);
     struct Point(i32, i32, i32);
@@ -583,7 +620,7 @@ fn _02_03_03_tuple_struct() {
 }
 
 /// 枚举
-#[test]
+#[cfg_attr(test, test)]
 fn _02_03_04_enum() {
 
     enum Number {
@@ -628,8 +665,57 @@ fn _02_03_04_enum() {
     println!("{:?}", v);
 }
 
+/// 用`#[derive(EnumReflect)]`代替手写的`as`转换：
+/// 生成变体名字`VARIANTS`、个数`COUNT`、`name()`、按声明顺序遍历的`iter()`，
+/// 以及从判别式转回枚举的`from_discriminant`和各个整数类型的`TryFrom`，显式指定的判别式同样有效。
+/// `from_discriminant`的参数是枚举`#[repr(..)]`指定的整数类型，没有指定时是`isize`。
+#[cfg_attr(test, test)]
+fn _02_03_04_enum_reflect() {
+    use std::convert::TryFrom;
+    use dive_into_rust_derive::EnumReflect;
+
+    #[derive(EnumReflect, Debug, PartialEq)]
+    enum Foo {
+        Bar,
+        Baz = 123,
+        Quux,
+    }
+
+    assert_eq!(Foo::VARIANTS, &["Bar", "Baz", "Quux"]);
+    assert_eq!(Foo::COUNT, 3);
+    assert_eq!(Foo::Quux.name(), "Quux");
+    assert_eq!(Foo::iter().map(|variant| variant as isize).collect::<Vec<_>>(), vec![0, 123, 124]);
+
+    assert_eq!(Foo::from_discriminant(124), Some(Foo::Quux));
+    assert_eq!(Foo::from_discriminant(1), None);
+    assert_eq!(Foo::try_from(123u32), Ok(Foo::Baz));
+    assert_eq!(Foo::try_from(-1i64), Err(-1));
+
+    #[derive(EnumReflect, Debug, PartialEq)]
+    enum Animal {
+        Dog = 1,
+        Cat = 2 | 3 | 4,
+        Tiger,
+    }
+
+    assert_eq!(Animal::try_from(8u8), Ok(Animal::Tiger));
+    assert_eq!(Animal::iter().rev().map(|animal| animal.name()).collect::<Vec<_>>(), vec!["Tiger", "Cat", "Dog"]);
+
+    // 超出`isize`范围的判别式，按`isize`比较会变成负数
+    #[derive(EnumReflect, Debug, PartialEq)]
+    #[repr(u64)]
+    enum Flag {
+        Low = 1,
+        High = 1 << 63,
+    }
+
+    assert_eq!(Flag::from_discriminant(1 << 63), Some(Flag::High));
+    assert_eq!(Flag::try_from(-1i64), Err(-1));
+    assert_eq!(Flag::try_from(1u8), Ok(Flag::Low));
+}
+
 /// 类型递归定义
-#[test]
+#[cfg_attr(test, test)]
 fn _02_03_05_type_recursion() {
 
     struct Recursive {
@@ -637,3 +723,5 @@ fn _02_03_05_type_recursion() {
         rec: Box<Recursive>,    // 通过指针间接引用的方式，控制递归类型的内存
     }
 }
+
+include!(concat!(env!("OUT_DIR"), "/chap02_examples.rs"));
@@ -1,5 +1,5 @@
 
-#[test]
+#[cfg_attr(test, test)]
 fn _03_02_01_expression() {
 
     let x = 100;
@@ -32,8 +32,83 @@ fn _03_02_01_expression() {
     println!("Bit or: {}\n", f1() || f2());
 }
 
+///
+/// 用`#[trace]`代替手写的`println!`：进入函数时记录参数，返回时记录返回值和耗时，
+/// 嵌套调用按深度缩进。事件交给当前线程的sink，这里换成一个把事件收集起来的闭包。
+///
+#[cfg_attr(test, test)]
+fn _03_02_01_expression_trace() {
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use dive_into_rust_derive::trace;
+    use crate::trace::{self, Trace, TraceEvent};
+
+    #[trace]
+    fn f1() -> bool {
+        true
+    }
+
+    #[trace]
+    fn f2() -> bool {
+        false
+    }
+
+    #[trace]
+    fn both(x: i32, label: &str) -> bool {
+        x > 0 && f1() & f2()
+    }
+
+    // 函数体被包在一个只调用一次的闭包里，返回从`&mut`参数借出来的引用也没有问题
+    #[trace]
+    fn first(v: &mut Vec<i32>) -> &mut i32 {
+        &mut v[0]
+    }
+
+    #[trace]
+    fn last<'a>(v: &'a mut [i32]) -> &'a mut i32 {
+        v.last_mut().unwrap()
+    }
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let collected = events.clone();
+    let previous = trace::set_sink(move |event: &TraceEvent| {
+        // 耗时每次都不一样，只留下其余部分
+        let line = match *event {
+            TraceEvent::Exit { function, depth, result, .. } => {
+                format!("{:indent$}<- {} = {}", "", function, result.unwrap_or("?"), indent = depth * 2)
+            }
+            ref event => event.to_string(),
+        };
+        collected.borrow_mut().push(line);
+    });
+
+    assert!(!(f2() && f1()));
+    assert!(!both(1, "bit and"));
+    let mut numbers = vec![1, 2];
+    *first(&mut numbers) += 10;
+    *last(&mut numbers) += 20;
+    trace::restore_sink(previous);
+    assert_eq!(numbers, [11, 22]);
+
+    assert_eq!(*events.borrow(), [
+        "-> f2()",
+        "<- f2 = false",
+        "-> both(x = 1, label = \"bit and\")",
+        "  -> f1()",
+        "  <- f1 = true",
+        "  -> f2()",
+        "  <- f2 = false",
+        "<- both = false",
+        "-> first(v = [1, 2])",
+        "<- first = 1",
+        "-> last(v = [11, 2])",
+        "<- last = 2",
+    ]);
+}
+
 /// 赋值表达式
-#[test]
+#[cfg_attr(test, test)]
 fn _03_02_02_assignment_expression() {
     // 声明局部变量，带mut修饰
     let mut x: i32 = 1;
@@ -55,7 +130,7 @@ fn _03_02_02_assignment_expression() {
 }
 
 /// 语句块表达式
-#[test]
+#[cfg_attr(test, test)]
 fn _03_02_03_statement_expression() {
 
     // 语句和表达式的区分方式是后面带不带分号(;)
@@ -74,7 +149,7 @@ fn _03_02_03_statement_expression() {
 }
 
 /// 条件语句
-#[test]
+#[cfg_attr(test, test)]
 fn _03_03_01_condition() {
 
     fn func(n: i32) {
@@ -94,7 +169,7 @@ fn _03_03_01_condition() {
 }
 
 /// 循环语句
-#[test]
+#[cfg_attr(test, test)]
 fn _03_03_01_loop() {
 
     let mut count = 0u32;
@@ -117,7 +192,7 @@ fn _03_03_01_loop() {
 }
 
 /// 带有生命周期标识符
-#[test]
+#[cfg_attr(test, test)]
 fn _03_03_01_loop_with_lifetime_specifier() {
     let mut m = 1;
     let n = 1;
@@ -137,7 +212,7 @@ fn _03_03_01_loop_with_lifetime_specifier() {
     }
 }
 
-#[test]
+#[cfg_attr(test, test)]
 fn _03_03_01_loop_expression() {
     let v = loop {
         break 10;
@@ -146,7 +221,7 @@ fn _03_03_01_loop_expression() {
 }
 
 /// while 语句是带条件判断的循环语句
-#[test]
+#[cfg_attr(test, test)]
 fn _03_03_01_while() {
 
     let mut n = 1;
@@ -166,10 +241,12 @@ fn _03_03_01_while() {
 }
 
 /// for循环
-#[test]
+#[cfg_attr(test, test)]
 fn _03_03_01_for() {
     let array = &[1, 2, 3, 4, 5];
     for i in array {
         println!("The number is {}", i);
     }
 }
+
+include!(concat!(env!("OUT_DIR"), "/chap03_examples.rs"));
@@ -1,5 +1,5 @@
 
-#[test]
+#[cfg_attr(test, test)]
 fn _04_01_01_function() {
 
     fn add1(t: (i32, i32)) -> i32 {
@@ -33,7 +33,7 @@ fn _04_01_01_function() {
 
 }
 
-#[test]
+#[cfg_attr(test, test)]
 fn _04_01_01_function_body() {
     static INNER_STATIC: i64 = 42;
 
@@ -58,8 +58,8 @@ fn _04_01_01_function_body() {
 
 /// 发散函数
 /// Rust支持一种特殊的发散函数(Diverging functions)，它的返回类型是感叹号`!`。
-#[test]
-#[should_panic]
+#[cfg_attr(test, test)]
+#[cfg_attr(test, should_panic)]
 fn _04_02_01_diverging_functions() {
 
     // 如果一个函数根本就不能正常返回，那么它可以这样写：
@@ -82,7 +82,7 @@ fn _04_02_01_diverging_functions() {
 
 /// const fn
 /// 函数可以用`const`关键字修饰，这样的函数可以在编译阶段被编译器执行，返回值也被视为编译期常量
-#[test]
+#[cfg_attr(test, test)]
 fn _04_04_01_const_fn() {
 
     #![feature(const_fn)]
@@ -96,7 +96,7 @@ fn _04_04_01_const_fn() {
 }
 
 /// 递归函数
-#[test]
+#[cfg_attr(test, test)]
 fn _04_05_01_recursive() {
 
     fn fib(index: u32) -> u64 {
@@ -112,4 +112,58 @@ fn _04_05_01_recursive() {
 
     // 当前版本的Rust暂时还不支持尾递归优化，
     // 因此，如果递归调用层次太多的话，是有可能撑爆栈空间的
-}
\ No newline at end of file
+}
+
+///
+/// 上面的`fib`会重复计算同样的参数，调用次数是指数级的。`#[memoize]`把结果按参数缓存起来，
+/// 递归调用也会先查缓存；缓存默认每个线程一份，`global`则所有线程共享，
+/// `capacity`限制缓存的条数，满了就丢掉最早的结果。同时生成`<函数名>_clear_cache`清空缓存。
+///
+#[cfg_attr(test, test)]
+fn _04_05_02_recursive_memoize() {
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use dive_into_rust_derive::memoize;
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[memoize]
+    fn fib(index: u32) -> u64 {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        if index == 1 || index == 2 {
+            1
+        } else {
+            fib(index - 1) + fib(index - 2)
+        }
+    }
+
+    assert_eq!(fib(90), 2880067194370816120);
+    assert_eq!(CALLS.load(Ordering::SeqCst), 90);
+    fib(90);
+    assert_eq!(CALLS.load(Ordering::SeqCst), 90);
+
+    fib_clear_cache();
+    fib(3);
+    assert_eq!(CALLS.load(Ordering::SeqCst), 93);
+
+    static LOOKUPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[memoize(global, capacity = 2)]
+    fn greet(name: String, excited: bool) -> String {
+        LOOKUPS.fetch_add(1, Ordering::SeqCst);
+        format!("Hello, {}{}", name, if excited { "!" } else { "." })
+    }
+
+    assert_eq!(greet("Rust".to_owned(), true), "Hello, Rust!");
+    let shared = std::thread::spawn(|| greet("Rust".to_owned(), true)).join().unwrap();
+    assert_eq!(shared, "Hello, Rust!");
+    assert_eq!(LOOKUPS.load(Ordering::SeqCst), 1);
+
+    // 容量是2，第三个结果会挤掉最早的那个
+    greet("Cargo".to_owned(), false);
+    greet("Clippy".to_owned(), false);
+    greet("Rust".to_owned(), true);
+    assert_eq!(LOOKUPS.load(Ordering::SeqCst), 4);
+}
+
+include!(concat!(env!("OUT_DIR"), "/chap04_examples.rs"));
@@ -1,6 +1,6 @@
 
 /// 成员方法
-#[test]
+#[cfg_attr(test, test)]
 fn _05_01_01_trait_method() {
 
     trait Shape {
@@ -64,7 +64,7 @@ fn _05_01_01_trait_method() {
 }
 
 /// 让trait的self是指针类型
-#[test]
+#[cfg_attr(test, test)]
 fn _05_01_01_boxing_self() {
 
     trait Shape {
@@ -89,7 +89,7 @@ fn _05_01_01_boxing_self() {
 }
 
 /// impl 的对象甚至可以是trait
-#[test]
+#[cfg_attr(test, test)]
 fn _05_01_01_impl_trait() {
     trait Shape {
         fn area(&self) -> f64;
@@ -130,7 +130,7 @@ fn _05_01_01_impl_trait() {
 /// 静态方法可以通过Type::FunctionName()的方式调用。
 ///
 /// 需要注意的是，即便第一个参数是Self相关类型，只要变量名不是self，就不能使用小数点的语法调用函数.
-#[test]
+#[cfg_attr(test, test)]
 fn _05_02_01_static_method() {
 
     struct T(i32);
@@ -154,7 +154,7 @@ fn _05_02_01_static_method() {
 }
 
 /// 静态函数
-#[test]
+#[cfg_attr(test, test)]
 fn _05_02_01_static_function() {
 
     // 无参数的函数，返回类型是实现该trait的具体类型
@@ -164,7 +164,7 @@ fn _05_02_01_static_function() {
 }
 
 /// 扩展方法
-#[test]
+#[cfg_attr(test, test)]
 fn _05_03_01_method_extension() {
 
     // 可以利用trait给其它类型添加成员方法
@@ -194,7 +194,7 @@ fn _05_03_01_method_extension() {
 /// 以前叫UFCS(universal function call syntax)，即“通用函数调用语法”。
 /// 它的具体写法为`<T as TraitName>::item`
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _05_04_01_ufcs() {
 
     trait Cook {
@@ -228,7 +228,7 @@ fn _05_04_01_ufcs() {
 }
 
 /// 函数和方法的调用本质上没有区别
-#[test]
+#[cfg_attr(test, test)]
 fn _05_04_02_method_function() {
     struct T(usize);
 
@@ -247,7 +247,7 @@ fn _05_04_02_method_function() {
 }
 
 /// trait约束和继承
-#[test]
+#[cfg_attr(test, test)]
 fn _05_05_01_trait_ad_hoc() {
 
     use std::fmt::Debug;
@@ -285,7 +285,7 @@ fn _05_05_01_trait_ad_hoc() {
 /// Rust里面为类型impl某些trait的时候，逻辑是非常机械化的。
 /// 为许多类型重复而单调地impl某些trait，是非常枯燥的事情。
 /// 为此，Rust提供了一个特殊的attribute，它可以帮我们自动impl某些trait
-#[test]
+#[cfg_attr(test, test)]
 fn _05_06_01_derive() {
 
     #[derive(Copy, Clone, Default, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -309,7 +309,7 @@ fn _05_06_01_derive() {
 }
 
 /// trait 别名
-#[test]
+#[cfg_attr(test, test)]
 fn _05_07_01_trait_alias() {
 
 
@@ -326,7 +326,7 @@ fn _05_07_01_trait_alias() {
 }
 
 /// 标准库常见trait
-#[test]
+#[cfg_attr(test, test)]
 fn _05_08_01_display_and_debug() {
 
     use std::fmt::{Display, Formatter, Error};
@@ -349,9 +349,58 @@ fn _05_08_01_display_and_debug() {
     println!("{:#?}", var);
 }
 
+/// 手写`impl Display`的格式字符串也可以放到属性里，由`#[derive(Display)]`生成实现。
+/// 占位符里写字段名（元组结构体写下标），`{field:?}`这样的格式说明照常可用。
+/// 字段名写错了会在编译期报错，泛型字段会自动加上对应的`Display`/`Debug`约束。
+/// 宽度和精度也可以取自别的字段，比如`{x:>width$}`，这个字段必须是`usize`类型。
+#[cfg_attr(test, test)]
+fn _05_08_01_display_derive() {
+
+    use dive_into_rust_derive::Display;
+
+    #[derive(Display)]
+    #[display("{{ field1: {field1}, field2: {field2} }}")]
+    struct T {
+        field1: i32,
+        field2: i32,
+    }
+
+    #[derive(Display)]
+    #[display("({0:.1}, {1:.1})")]
+    struct Point(f64, f64);
+
+    #[derive(Display)]
+    enum Shape<U> {
+        #[display("circle r={radius}{unit}")]
+        Circle { radius: f64, unit: U },
+        #[display("polygon {0:?}")]
+        Polygon(Vec<Point2>),
+        Empty,
+    }
+
+    #[derive(Debug)]
+    struct Point2(i32, i32);
+
+    #[derive(Display)]
+    #[display("[{name:>width$}] {value:.prec$}")]
+    struct Cell {
+        name: &'static str,
+        value: f64,
+        width: usize,
+        prec: usize,
+    }
+
+    assert_eq!(T { field1: 1, field2: 2 }.to_string(), "{ field1: 1, field2: 2 }");
+    assert_eq!(Point(0.25, 2.0).to_string(), "(0.2, 2.0)");
+    assert_eq!(Shape::Circle { radius: 1.5, unit: "cm" }.to_string(), "circle r=1.5cm");
+    assert_eq!(Shape::<&str>::Polygon(vec![Point2(0, 0), Point2(1, 1)]).to_string(), "polygon [Point2(0, 0), Point2(1, 1)]");
+    assert_eq!(format!("[{:>8}]", Shape::<&str>::Empty.to_string()), "[   Empty]");
+    assert_eq!(Cell { name: "x", value: 1.23456, width: 4, prec: 2 }.to_string(), "[   x] 1.23");
+}
+
 /// 全序关系
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _05_08_02_ord_eq() {
 
     // 对于集合X中的元素a,b,c
@@ -384,14 +433,14 @@ fn _05_08_02_ord_eq() {
 /// 它定义在std::marker模块中，没有任何成员方法。
 /// 它有`#[lang="sized"]`属性，它是lang级别的，用户不能impl这trait。
 /// 一个类型是否满足Sized约束完全由编译器推导的，用户无权指定
-#[test]
+#[cfg_attr(test, test)]
 fn _05_08_03_sized() {
 
 }
 
 /// Rust中没有“构造函数”的概念。因为，相比普通函数，构造函数本身并没有提供什么额外的抽象能力。
 /// 所以默认所谓的“默认构造函数”，但可以通过impl Default来实现默认值。
-#[test]
+#[cfg_attr(test, test)]
 fn _05_08_04_default() {
 
     // 标准库提供了Default trait来处理无参数、无错误处理的“默认值”
@@ -401,4 +450,6 @@ fn _05_08_04_default() {
 
     // 标准库中很多类型都实现了这个trait，它相当于提供了一个类型的默认值。
     // Rust中，单词new并不是关键字，
-}
\ No newline at end of file
+}
+
+include!(concat!(env!("OUT_DIR"), "/chap05_examples.rs"));
@@ -8,7 +8,7 @@
 /// 表示方式：`[T;n]`
 /// `T`表示元素类型
 /// `n`表示元素个数
-#[test]
+#[cfg_attr(test, test)]
 fn _06_01_01_array() {
     // 定长数组
     let xs: [i32; 5] = [1, 2, 3, 4, 5];
@@ -17,7 +17,7 @@ fn _06_01_01_array() {
 }
 
 /// 同类型的数组之间可以相互赋值
-#[test]
+#[cfg_attr(test, test)]
 fn _06_01_02_array() {
     let mut xs: [i32; 5] = [1, 2, 3, 4, 5];
     let ys: [i32; 5] = [6, 7, 8, 9, 10];
@@ -28,7 +28,7 @@ fn _06_01_02_array() {
 /// 把数组xs作为参数传给一个函数，这个数组并不会退化成一个指针
 /// 而是将这个数组完整复制进这个函数。函数体内对数组的改动不会影响到外面的数组
 /// Rust支持usize类型的索引的数组，索引从0开始计数
-#[test]
+#[cfg_attr(test, test)]
 fn _06_01_03_array() {
     let v: [i32; 5] = [1, 2, 3, 4, 5];
     let x = v[0] + v[1];    // 把第一个元素和第二个元素的值相加
@@ -36,7 +36,7 @@ fn _06_01_03_array() {
 }
 
 /// 内置方法
-#[test]
+#[cfg_attr(test, test)]
 fn _06_01_04_array() {
 
     // 只要包含元素，数组是可比较的
@@ -55,7 +55,7 @@ fn _06_01_04_array() {
 }
 
 /// 多维数组
-#[test]
+#[cfg_attr(test, test)]
 fn _06_01_05_array() {
 
     let v: [[i32; 2]; 3] = [[0,0],[0,0],[0,0]];
@@ -69,7 +69,7 @@ fn _06_01_05_array() {
 /// 对数组借用borrow操作，可以生成一个“数组切片”(Slice)。
 /// 数组切片对数组没有“所有权”，我们可以把数组切片看做专门用于指向数组的指针，
 /// 是对数组的另外一个“视图”。
-#[test]
+#[cfg_attr(test, test)]
 fn _06_01_06_array() {
     // 比如，有一个数组`[T;n]`，它的借用指针的类型就是`&[T;n]`。
     // 它在编译器内部转换为数组切片类型`&[T]`。
@@ -94,7 +94,7 @@ fn _06_01_06_array() {
 
 /// Range
 /// Rust中的Range代表一个“区间”，一个“范围”，它有内置的语法支持，就是两个小数点`..`。
-#[test]
+#[cfg_attr(test, test)]
 fn _06_02_01_range() {
     // r是一个Range<i32>，中间是两个点，代表`[1, 10)`这个区间
     let r = 1..10;
@@ -104,7 +104,7 @@ fn _06_02_01_range() {
 }
 
 /// Range
-#[test]
+#[cfg_attr(test, test)]
 fn _06_02_02_range() {
     use std::ops::Range;
     let r = Range { start: 1, end: 10 };
@@ -117,7 +117,7 @@ fn _06_02_02_range() {
 /// 两个小数点的语法仅仅是一个“语法糖”而已，用它构造出来的变量是Range类型
 /// 这个类型本身实现了Iterator trait，因此它可以直接应用到循环语句中
 /// Range具有迭代器的全部功能，因此它能调用迭代器的成员方法。
-#[test]
+#[cfg_attr(test, test)]
 fn _06_02_03_range() {
     use std::iter::Iterator;
     // 先用rev方法把这个区间反过来，然后用map方法把每个元素乘以10
@@ -133,7 +133,7 @@ fn _06_02_03_range() {
 /// `std::ops::RangeTo` 代表没有起始只有结束的范围，语法为`..end`，对有符号数的含义是`(-∞, end)`，对无符号数的含义是`[0, end)`。
 /// `std::ops::RangeFull` 代表没有上下界限的范围，语法为`..`，对有符号数的含义是`(-∞, +∞)`，对无符号数的含义是`[0, +∞)`。
 /// 数组和Range之间最常用的配合就是使用Range进行索引操作
-#[test]
+#[cfg_attr(test, test)]
 fn _06_02_04_slice() {
 
     fn print_slice(arr: &[i32]) {
@@ -161,7 +161,7 @@ fn _06_02_04_slice() {
 }
 
 /// 边界检查
-#[test]
+#[cfg_attr(test, test)]
 fn _06_03_01_bound() {
     // index超过了数组的真实长度范围，会执行`panic!`操作，导致线程abort
     // 为了防止索引操作导致程序崩溃，如果我们不确定使用的“索引”是否合法，
@@ -176,7 +176,7 @@ fn _06_03_01_bound() {
 
 /// 从效率上看，Rust比C/C++的数组索引效率低一点，因为C/C++的索引操作是不执行任何安全性检查的，它们
 /// 对应的Rust代码相当于调用`get_unchecked()`函数。在Rust中，更加地道的做法是尽量使用“迭代器”方法。
-#[test]
+#[cfg_attr(test, test)]
 fn _06_03_02_iterator() {
     use std::iter::Iterator;
 
@@ -194,7 +194,7 @@ fn _06_03_02_iterator() {
 /// Rust的字符串涉及两种类型，
 /// 一种是`&str`
 /// 一种是`String`
-#[test]
+#[cfg_attr(test, test)]
 fn _06_04_01_string() {
     // `str`是DST类型
     // `&str`是字符串切片类型
@@ -212,7 +212,7 @@ fn _06_04_01_string() {
 /// `String` 类型
 /// 它跟&str类型的主要区别是，它有管理内存空间的权力。
 /// `&str`类型是对一块字符串区间的借用，它对所指向的内存空间没有所有权，哪怕`&mut str`也一样
-#[test]
+#[cfg_attr(test, test)]
 fn _06_04_02_string() {
     let greeting: &str = "Hello";
     // 我们没有办法扩大greeting所引用的范围，在它后面增加内容。
@@ -239,3 +239,5 @@ fn _06_04_02_string() {
     // 而实际参数是`&mut String`类型，这里编译器给我们做了自动类型转换
     // 在capitalize函数内部，它有权修改`&mut str`所指向的内容，但是无权给这个字符串扩容或者释放内存
 }
+
+include!(concat!(env!("OUT_DIR"), "/chap06_examples.rs"));
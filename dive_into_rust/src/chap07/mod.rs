@@ -9,7 +9,7 @@
 /// - Rust的“模式解构”与Rust的核心所有权管理功能完全相容。
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _07_01_01_pattern_destructure() {
 
     let tuple = (1_i32, false, 3f32);
@@ -43,7 +43,7 @@ fn _07_01_01_pattern_destructure() {
 
 
 /// 模式解构 match
-#[test]
+#[cfg_attr(test, test)]
 fn _07_01_02_pattern_destructure_match() {
 
     enum Direction {
@@ -67,7 +67,7 @@ fn _07_01_02_pattern_destructure_match() {
 }
 
 /// 下划线
-#[test]
+#[cfg_attr(test, test)]
 fn _07_01_03_pattern_destructure_underscore() {
     struct P(f32, f32, f32);
     // 参数类型是P，参数本身是一个模式，解构之后，变量x，y分别绑定了第一个和第三个成员
@@ -107,7 +107,7 @@ fn _07_01_03_pattern_destructure_underscore() {
 }
 
 /// match也是表达式
-#[test]
+#[cfg_attr(test, test)]
 fn _07_01_04_match_expression() {
 
     enum Direction {
@@ -164,7 +164,7 @@ fn _07_01_04_match_expression() {
 }
 
 /// “匹配看守(match guards)”
-#[test]
+#[cfg_attr(test, test)]
 fn _07_01_05_guards() {
 
     enum OptionalInt {
@@ -198,7 +198,7 @@ fn _07_01_05_guards() {
 /// 变量绑定
 /// 变量绑定使用`@`符号，`@`符号前面是新声明的变量，后面是需要匹配的模式
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _07_01_06_variable_binding() {
 
     let x = 1;
@@ -233,7 +233,7 @@ fn _07_01_06_variable_binding() {
 }
 
 /// 引用绑定
-#[test]
+#[cfg_attr(test, test)]
 fn _07_01_07_reference_binding() {
 
     let x = 5_i32;
@@ -254,10 +254,16 @@ fn _07_01_07_reference_binding() {
     // 实际参数的类型肯定不是unit，此处必定有编译错误，通过编译错误，可以看到实参的具体类型
     // ILLEGAL: type_id(x);
 
+    // `std::any::type_name`是稳定版本的写法，nightly上仍然可以直接调用编译器内置的intrinsic，
+    // 不过现在它只能在编译期求值，必须放在`const`块里
+    #[cfg(nightly)]
     fn print_type_name<T>(_arg: &T) -> &'static str {
-        unsafe {
-            return std::intrinsics::type_name::<T>();
-        }
+        const { std::intrinsics::type_name::<T>() }
+    }
+
+    #[cfg(not(nightly))]
+    fn print_type_name<T>(_arg: &T) -> &'static str {
+        std::any::type_name::<T>()
     }
 
     let ref x = 5_i32;
@@ -268,7 +274,7 @@ fn _07_01_07_reference_binding() {
 /// mut关键字和ref关键字一样，是“模式”的一部分
 /// Rust中，所有的变量绑定默认都是“不可更改”的。
 /// 只有使用了`mut`修饰的变量绑定才能修改数据
-#[test]
+#[cfg_attr(test, test)]
 fn _07_01_08_mut_binding() {
 
     let mut v = vec![1i32, 2, 3];
@@ -289,7 +295,7 @@ fn _07_01_08_mut_binding() {
 }
 
 /// if-let和while-let模式匹配
-#[test]
+#[cfg_attr(test, test)]
 fn _07_01_09_if_let_while_let() {
     enum E<T> {
         A(T), B(T), C, D, E, F
@@ -319,7 +325,7 @@ fn _07_01_09_if_let_while_let() {
 }
 
 /// 函数和闭包参数做模式解构
-#[test]
+#[cfg_attr(test, test)]
 fn _07_01_10_closure_pattern() {
     // 函数可以接受一个结构体参数做模式解构的
     struct T {
@@ -339,3 +345,4 @@ fn _07_01_10_closure_pattern() {
     assert_eq!(('A', false), test(x));
 }
 
+include!(concat!(env!("OUT_DIR"), "/chap07_examples.rs"));
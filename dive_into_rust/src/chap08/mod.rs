@@ -20,22 +20,24 @@
 /// monad 在函数式编程中的实现，主要是用来控制副作用(side effect)
 ///
 
-#[test]
+#[cfg_attr(test, test)]
 fn _08_01_01_algebraic_data_type() {
 
 }
 
 /// Never Type
 /// 
-#[test]
+#[cfg_attr(test, test)]
 fn _08_01_02_never_type() {
 
     fn call_fn<T, F: Fn(i32) -> T> (f: F, arg: i32) -> T { f (arg) }
     // 如果不把`!`当成一个类型，那么下面这句话会出现编译错误，因为只有类型才能替换类型参数
-    call_fn(std::process::exit, 0);
+    // 真的调用会让整个进程退出，连同运行它的测试一起，所以这里只让它通过类型检查
+    let _exit = || call_fn(std::process::exit, 0);
 }
 
-#[test]
+#[cfg_attr(test, test)]
+#[cfg_attr(test, should_panic)]
 fn _08_01_03_never_type() {
     let t = std::thread::spawn(||panic!("nope"));
     t.join().unwrap();
@@ -43,22 +45,28 @@ fn _08_01_03_never_type() {
     // println永远不可能执行，因为`t.join().unwrap()`会产生一个`!`类型
 }
 
-#[test]
+#[cfg_attr(test, test)]
 fn _08_01_04_never_type() {
     use std::str::FromStr;
     use std::mem::{size_of, size_of_val};
 
+    // `!`类型还没有稳定，stable上用标准库中同样没有任何值的`Infallible`代替
+    #[cfg(nightly)]
+    type Never = !;
+    #[cfg(not(nightly))]
+    type Never = std::convert::Infallible;
+
     struct T(String);
 
     impl FromStr for T {
-        type Err = !;
+        type Err = Never;
 
-        fn from_str(s: &str) -> Result<T, !> {
+        fn from_str(s: &str) -> Result<T, Never> {
             Ok(T(String::from(s)))
         }
     }
 
-    let r: Result<T, !> = T::from_str("hello");
+    let r: Result<T, Never> = T::from_str("hello");
     println!("Size of T: {}", size_of::<T>());
     println!("Size of Result: {}", size_of_val(&r));
     // 将来甚至应该可以直接用let语句进行模式匹配而不发生编译错误
@@ -67,32 +75,44 @@ fn _08_01_04_never_type() {
     // println!("{}", s);
 }
 
+/// 手写的`FromStr`也可以用`#[derive(FromStr)]`生成：
+/// 只有一个字段的结构体直接把整个字符串交给字段解析，多个字段用`#[from_str(sep = ",")]`指定分隔符；
+/// 无字段的枚举按变体名解析，加上`#[from_str(case_insensitive)]`就不区分大小写。
+/// 错误类型`Parse<类型名>Error`会一并生成，能指出是哪个字段解析失败。
+#[cfg_attr(test, test)]
+fn _08_01_04_from_str_derive() {
+    use std::str::FromStr;
+    use dive_into_rust_derive::FromStr;
 
+    #[derive(FromStr)]
+    struct T(String);
 
+    #[derive(FromStr, Debug, PartialEq)]
+    #[from_str(sep = ",")]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
 
+    #[derive(FromStr, Debug, PartialEq)]
+    #[from_str(case_insensitive)]
+    enum Direction {
+        North,
+        South,
+    }
 
+    let T(s) = T::from_str("hello").unwrap();
+    assert_eq!(s, "hello");
 
+    assert_eq!("1,2".parse::<Point>().unwrap(), Point { x: 1, y: 2 });
+    match "1,two".parse::<Point>() {
+        Err(ParsePointError::Invalid { field, input, .. }) => assert_eq!((field, input.as_str()), ("y", "two")),
+        _ => panic!("expected an invalid `y`"),
+    }
+    assert_eq!("1".parse::<Point>().unwrap_err().to_string(), "Missing value for field `y`");
 
+    assert_eq!("SOUTH".parse(), Ok(Direction::South));
+    assert_eq!("East".parse::<Direction>(), Err(ParseDirectionError { input: "East".to_owned() }));
+}
 
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
+include!(concat!(env!("OUT_DIR"), "/chap08_examples.rs"));
@@ -6,7 +6,7 @@
 /// 3. 自动代码生成
 /// 4. 实现语法扩展
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _09_01_01_macro() {
 
     println!("number1 {} number2 {}", 1, 2); // 编译期检查、计算
@@ -27,10 +27,9 @@ fn _09_01_01_macro() {
 ///
 /// `+`代表一个或多个重复，`*`代表零个或多个重复
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _09_01_02_macro() {
 
-    #[macro_export]
     macro_rules! hashmap {
         ($($key: expr => $val: expr), *) =>
         {{
@@ -58,7 +57,7 @@ fn _09_01_02_macro() {
 /// 前面说过，可以用`derive`帮我们自动`impl`某些`trait`。`derive`是一种编译器扩展，可以帮我们实现宏功能
 /// Rust目前推出了macro 1.1版本，它还不是一个稳定的版本，最终版本会被macro 2.0代替。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _09_01_03_macro() {
 
     use dive_into_rust_derive::AnswerFn;
@@ -67,4 +66,498 @@ fn _09_01_03_macro() {
     struct Struct;
 
     assert_eq!(42, answer());
-}
\ No newline at end of file
+}
+
+///
+/// 过程宏能做的事情远不止生成一个函数，`Render`会在编译期读取模板文件，为结构体生成`render`方法。
+///
+/// `{{#each items}} ... {{/each}}`遍历集合字段，块内用`{{this}}`、`{{this.name}}`访问当前元素，用`{{@index}}`访问下标。
+/// 只要`&items`实现了`IntoIterator`，不管是`Vec`、数组还是切片都可以遍历。
+/// 块可以嵌套，内层的`{{#each this}}`遍历的是外层的当前元素。
+///
+#[cfg_attr(test, test)]
+fn _09_01_04_render_each() {
+
+    use dive_into_rust_derive::Render;
+    use dive_into_rust_template::Render;
+
+    struct Line {
+        name: String,
+        price: String,
+    }
+
+    #[derive(Render)]
+    #[TemplateName = "templates/09_01_04_each.tpl"]
+    struct Report {
+        title: String,
+        tags: Vec<String>,
+        lines: Vec<Line>,
+        grid: Vec<Vec<String>>,
+    }
+
+    let report = Report {
+        title: "Fruit".to_owned(),
+        tags: vec!["fresh".to_owned(), "cheap".to_owned()],
+        lines: vec![
+            Line { name: "apple".to_owned(), price: "3".to_owned() },
+            Line { name: "pear".to_owned(), price: "5".to_owned() },
+        ],
+        grid: vec![vec!["a".to_owned(), "b".to_owned()], vec!["c".to_owned()]],
+    };
+
+    assert_eq!(report.render(), "# Fruit\n[fresh][cheap]\n0. apple: 3\n1. pear: 5\n\n(a,b,)(c,)\n");
+}
+
+///
+/// `{{#if flag}}...{{else}}...{{/if}}`根据`bool`字段选择分支，
+/// `{{#with opt}}...{{else}}...{{/with}}`在`Option`为`Some`时展开，块内的`{{this}}`就是`Some`里面的值。
+///
+/// 生成的代码分别是`if`和`if let`，预先计算长度的时候也只会累加实际走到的那个分支。
+///
+#[cfg_attr(test, test)]
+fn _09_01_05_render_if_with() {
+
+    use dive_into_rust_derive::Render;
+    use dive_into_rust_template::Render;
+
+    struct Member {
+        name: String,
+        active: bool,
+        nickname: Option<String>,
+    }
+
+    #[derive(Render)]
+    #[TemplateName = "templates/09_01_05_if_with.tpl"]
+    struct Team {
+        name: String,
+        admin: bool,
+        email: Option<String>,
+        members: Vec<Member>,
+    }
+
+    let mut team = Team {
+        name: "rust".to_owned(),
+        admin: true,
+        email: Some("team@rust-lang.org".to_owned()),
+        members: vec![
+            Member { name: "alice".to_owned(), active: true, nickname: Some("al".to_owned()) },
+            Member { name: "bob".to_owned(), active: false, nickname: None },
+        ],
+    };
+    assert_eq!(team.render(), "rust (admin)\n<team@rust-lang.org>\n+alice aka al;-bob;\n");
+
+    team.admin = false;
+    team.email = None;
+    assert_eq!(team.render(), "rust (guest)\nno email\n+alice aka al;-bob;\n");
+}
+
+///
+/// 插值的字段不必是`String`，任何实现了`Display`的类型都可以，比如数字、枚举或者自定义类型；
+/// 用`{{a.b}}`这样的点号路径还可以访问嵌套结构体的字段，`{{a.0}}`访问元组的元素。
+///
+/// 字符串类型的长度是确定的，其他类型只能估算一个容量，放不下的时候`String`会自己扩容。
+///
+#[cfg_attr(test, test)]
+fn _09_01_06_render_display() {
+
+    use std::fmt;
+    use dive_into_rust_derive::Render;
+    use dive_into_rust_template::Render;
+
+    enum Level {
+        Junior,
+        Senior,
+    }
+
+    impl fmt::Display for Level {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Level::Junior => write!(f, "junior"),
+                Level::Senior => write!(f, "senior"),
+            }
+        }
+    }
+
+    struct Address {
+        city: String,
+        location: (f64, f64),
+    }
+
+    #[derive(Render)]
+    #[TemplateName = "templates/09_01_06_display.tpl"]
+    struct Employee {
+        name: String,
+        age: u32,
+        level: Level,
+        address: Address,
+        scores: Vec<u8>,
+    }
+
+    let employee = Employee {
+        name: "alice".to_owned(),
+        age: 30,
+        level: Level::Senior,
+        address: Address { city: "Shenzhen".to_owned(), location: (22.5, 114.1) },
+        scores: vec![90, 85],
+    };
+
+    assert_eq!(employee.render(), "alice (30, senior) lives in Shenzhen, 22.5/114.1\n0=90 1=85 \n");
+    assert_eq!(Level::Junior.to_string(), "junior");
+}
+
+///
+/// 生成HTML的时候，字段里的`<`、`&`之类的字符需要转义，否则会破坏页面结构，甚至被用来注入脚本。
+///
+/// `#[TemplateEscape = "html"]`会把插值里的`&<>"'`替换成实体，`"xml"`用`&apos;`表示单引号，`"none"`是默认值，不做转义。
+/// 可信的标记可以用三个大括号`{{{body}}}`原样输出。
+///
+#[cfg_attr(test, test)]
+fn _09_01_07_render_escape() {
+
+    use dive_into_rust_derive::Render;
+    use dive_into_rust_template::Render;
+
+    #[derive(Render)]
+    #[TemplateName = "templates/09_01_07_escape.tpl"]
+    #[TemplateEscape = "html"]
+    struct Article {
+        title: String,
+        author: String,
+        body: String,
+        comments: Vec<String>,
+    }
+
+    let article = Article {
+        title: "Tom & Jerry".to_owned(),
+        author: "\"Hanna\" & 'Barbera'".to_owned(),
+        body: "<b>cat</b> and <i>mouse</i>".to_owned(),
+        comments: vec!["<script>alert(1)</script>".to_owned()],
+    };
+
+    assert_eq!(article.render(), "<h1>Tom &amp; Jerry</h1>\n\
+        <p title=\"&quot;Hanna&quot; &amp; &#x27;Barbera&#x27;\"><b>cat</b> and <i>mouse</i></p>\n\
+        <ul><li>&lt;script&gt;alert(1)&lt;/script&gt;</li></ul>\n");
+}
+
+///
+/// `TemplateName`的路径是相对于当前crate的`Cargo.toml`所在目录解析的，和在哪里执行`cargo`无关。
+/// 生成的代码会用`include_str!`引用模板文件，所以模板改动之后cargo会重新编译。
+///
+/// 简短的模板也可以直接用`#[Template = "..."]`写在结构体上，不需要单独的文件。
+///
+#[cfg_attr(test, test)]
+fn _09_01_08_render_inline() {
+
+    use dive_into_rust_derive::Render;
+    use dive_into_rust_template::Render;
+
+    #[derive(Render)]
+    #[Template = "Hello, {{name}}!{{#if admin}} (admin){{/if}}"]
+    struct Greeting {
+        name: String,
+        admin: bool,
+    }
+
+    let greeting = Greeting { name: "world".to_owned(), admin: false };
+    assert_eq!(greeting.render(), "Hello, world!");
+}
+
+///
+/// 重复的页头页脚可以拆成单独的模板文件，再用`{{> partials/header.tpl}}`引入。
+///
+/// 被引入的文件在编译期展开，路径相对于引入它的那个模板；它和引入处共享同样的字段作用域，
+/// 所以在`{{#each}}`里面引入的模板也能用`{{this}}`。模板互相引入形成循环的时候会报编译错误。
+///
+#[cfg_attr(test, test)]
+fn _09_01_09_render_partial() {
+
+    use dive_into_rust_derive::Render;
+    use dive_into_rust_template::Render;
+
+    #[derive(Render)]
+    #[TemplateName = "templates/09_01_09_page.tpl"]
+    struct Page {
+        title: String,
+        items: Vec<String>,
+    }
+
+    let page = Page {
+        title: "Todo".to_owned(),
+        items: vec!["read".to_owned(), "write".to_owned()],
+    };
+
+    assert_eq!(page.render(), "== Todo ==\n\n0: read\n1: write\n\n-- Todo --\n");
+}
+
+///
+/// 插值后面可以用`|`接上过滤器，对渲染出来的字符串再做加工，多个过滤器从左到右依次执行。
+///
+/// 内置的过滤器有`upper`、`lower`、`trim`、`pad_left(宽度, 填充字符)`和`truncate(长度)`，
+/// 也可以用`#[TemplateFilter(名字 = 函数路径)]`注册自己的函数，它接收`&str`和模板里写的参数，返回`String`。
+///
+#[cfg_attr(test, test)]
+fn _09_01_10_render_filter() {
+
+    use dive_into_rust_derive::Render;
+    use dive_into_rust_template::Render;
+
+    fn slugify(value: &str) -> String {
+        value.split_whitespace().collect::<Vec<_>>().join("-")
+    }
+
+    #[derive(Render)]
+    #[TemplateName = "templates/09_01_10_filter.tpl"]
+    #[TemplateFilter(slug = slugify)]
+    struct Ticket {
+        name: String,
+        id: u32,
+        summary: String,
+        tags: Vec<String>,
+    }
+
+    let ticket = Ticket {
+        name: "  crash on start ".to_owned(),
+        id: 42,
+        summary: "The application exits right after launch".to_owned(),
+        tags: vec!["Needs Triage".to_owned(), "Bug".to_owned()],
+    };
+
+    assert_eq!(ticket.render(), "CRASH ON START #00000042\nThe applica...\nneeds-triage bug \n");
+}
+
+///
+/// 枚举也可以`derive(Render)`，每个成员用自己的`#[Template]`或者`#[TemplateName]`，
+/// 生成的`render`方法先`match self`，再按匹配到的成员的模板渲染。
+///
+/// 结构体风格的成员按字段名引用，元组风格的成员和元组结构体一样，用`{{0}}`、`{{1}}`按位置引用。
+/// `match`绑定出来的字段是引用，`{{#each}}`、`{{#if}}`、`{{#with}}`对它们照样可用。
+///
+#[cfg_attr(test, test)]
+fn _09_01_11_render_enum() {
+
+    use dive_into_rust_derive::Render;
+    use dive_into_rust_template::Render;
+
+    #[derive(Render)]
+    enum Shape {
+        #[Template = "circle r={{radius}}"]
+        Circle { radius: f64 },
+        #[Template = "rect {{0}}x{{1}}"]
+        Rect(u32, u32),
+        #[Template = "empty"]
+        Empty,
+        #[Template = "polygon{{#each points}} ({{this.0}},{{this.1}}){{/each}}{{#if closed}} closed{{/if}}{{#with label}} [{{this}}]{{else}} unnamed{{/with}}"]
+        Polygon { points: Vec<(i32, i32)>, closed: bool, label: Option<String> },
+        #[Template = "rows{{#each 0}} [{{#each this}}{{this}}{{/each}}]{{/each}}"]
+        Rows(Vec<Vec<u8>>),
+    }
+
+    #[derive(Render)]
+    #[Template = "{{0}} = {{1}}"]
+    struct Pair(String, i32);
+
+    assert_eq!(Shape::Circle { radius: 1.5 }.render(), "circle r=1.5");
+    assert_eq!(Shape::Rect(3, 4).render(), "rect 3x4");
+    assert_eq!(Shape::Empty.render(), "empty");
+    let triangle = Shape::Polygon { points: vec![(0, 0), (1, 0), (0, 1)], closed: true, label: Some("abc".to_owned()) };
+    assert_eq!(triangle.render(), "polygon (0,0) (1,0) (0,1) closed [abc]");
+    let line = Shape::Polygon { points: vec![(0, 0), (2, 2)], closed: false, label: None };
+    assert_eq!(line.render(), "polygon (0,0) (2,2) unnamed");
+    assert_eq!(Shape::Rows(vec![vec![1, 2], vec![], vec![3]]).render(), "rows [12] [] [3]");
+    assert_eq!(Pair("answer".to_owned(), 42).render(), "answer = 42");
+}
+
+///
+/// 带泛型参数和生命周期的类型也可以`derive(Render)`，生成的`impl`会带上同样的泛型参数和`where`子句。
+///
+/// 模板里直接插值的泛型字段会自动加上`Display`约束，所以`Page<T>`里的`T`不用事先声明`T: Display`，
+/// 只有在真正渲染的时候才要求`T`能被显示。
+///
+#[cfg_attr(test, test)]
+fn _09_01_12_render_generic() {
+
+    use dive_into_rust_derive::Render;
+    use dive_into_rust_template::Render;
+
+    #[derive(Render)]
+    #[Template = "{{title}}: {{#each items}}{{this}};{{/each}} {{#with footer}}{{this}}{{/with}}"]
+    struct Page<'a, T, F> where F: Clone {
+        title: &'a str,
+        items: Vec<T>,
+        footer: Option<F>,
+    }
+
+    let page = Page { title: "primes", items: vec![2, 3, 5], footer: Some('!') };
+    assert_eq!(page.render(), "primes: 2;3;5; !");
+}
+
+///
+/// 同样的模板语法也可以在运行期使用：`Template::parse`或者`Template::from_file`解析模板，
+/// `render`的时候再按名字从`Context`里面查找字段，`HashMap<&str, Value>`就是一个现成的`Context`。
+///
+/// 字段写错了编译期不会发现，而是由`render`返回带行号和列号的错误。
+///
+#[cfg_attr(test, test)]
+fn _09_01_13_runtime_template() {
+
+    use std::collections::HashMap;
+    use dive_into_rust_template::{Escape, Template, ToValue, Value};
+
+    // 运行期的路径相对于当前目录，所以这里拼上crate所在的目录，从哪里运行都能找到模板
+    let page = Template::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/templates/09_01_09_page.tpl")).unwrap();
+    let items = vec!["read", "write"];
+    let mut context = HashMap::new();
+    context.insert("title", "Todo".to_value());
+    context.insert("items", items.to_value());
+    assert_eq!(page.render(&context).unwrap(), "== Todo ==\n\n0: read\n1: write\n\n-- Todo --\n");
+
+    let greeting = Template::parse("<p>{{name | shout('!')}}</p>{{#with nickname}} ({{this}}){{/with}}")
+        .unwrap()
+        .escape(Escape::Html)
+        .filter("shout", |value, arguments| value.to_uppercase() + &arguments[0]);
+    let mut context = HashMap::new();
+    context.insert("name", "Tom & Jerry".to_value());
+    context.insert("nickname", Value::Null);
+    assert_eq!(greeting.render(&context).unwrap(), "<p>TOM &amp; JERRY!</p>");
+
+    let typo = Template::parse("Hello\n{{nmae}}").unwrap();
+    let err = typo.render(&context).unwrap_err();
+    assert_eq!(err.to_string(), "inline template:2:1: Could not locate field `nmae`");
+}
+
+///
+/// `render`会先算出总长度再一次性拼出`String`，文档很大的时候整个都要放在内存里。
+/// 派生出来的`render_to`可以把模板一段一段地写进任何`fmt::Write`，`write_to`则写进`io::Write`，
+/// 比如文件或者socket；派生同时还实现了`Display`，所以`format!`和`println!`也能直接用。
+///
+#[cfg_attr(test, test)]
+fn _09_01_14_render_stream() {
+
+    use std::io::Write;
+    use dive_into_rust_derive::Render;
+    use dive_into_rust_template::Render;
+
+    #[derive(Render)]
+    #[Template = "{{#each rows}}<{{this}}>{{/each}}"]
+    #[TemplateEscape = "html"]
+    struct Table {
+        rows: Vec<String>,
+    }
+
+    let table = Table { rows: vec!["a&b".to_owned(), "c".to_owned()] };
+
+    let mut text = String::new();
+    table.render_to(&mut text).unwrap();
+    assert_eq!(text, "<a&amp;b><c>");
+
+    let mut bytes: Vec<u8> = Vec::new();
+    table.write_to(&mut bytes).unwrap();
+    writeln!(bytes).unwrap();
+    assert_eq!(bytes, b"<a&amp;b><c>\n");
+
+    assert_eq!(format!("[{}]", table), "[<a&amp;b><c>]");
+    assert_eq!(table.render(), table.to_string());
+}
+
+///
+/// `Encode`和`Decode`把数据和`bytes`的缓冲区互相转换：整数小端序，`String`和`Vec`带`u32`长度前缀，
+/// 枚举先写一个字节的变体序号。类型上的`#[codec(version = N)]`会多写一个版本号，
+/// 字段上的`#[codec(since = N)]`表示这个字段是第N版才加的，解码旧数据时用`default`或者`Default::default()`补上。
+///
+#[cfg_attr(test, test)]
+fn _09_01_15_codec() {
+
+    use bytes::{Buf, BufMut, BytesMut, IntoBuf};
+    use dive_into_rust_derive::{Decode, Encode};
+    use crate::codec::{Decode, DecodeError, Encode};
+
+    #[derive(Encode, Decode, Debug, PartialEq)]
+    enum Shape {
+        Circle { radius: f64 },
+        Rect(u32, u32),
+        Empty,
+    }
+
+    #[derive(Encode, Decode, Debug, PartialEq)]
+    #[codec(version = 2)]
+    struct Drawing {
+        name: String,
+        shapes: Vec<Shape>,
+        #[codec(since = 2, default = Some("#000".to_owned()))]
+        color: Option<String>,
+    }
+
+    let drawing = Drawing {
+        name: "logo".to_owned(),
+        shapes: vec![Shape::Circle { radius: 1.5 }, Shape::Rect(3, 4), Shape::Empty],
+        color: None,
+    };
+    let bytes = drawing.to_bytes();
+    assert_eq!(bytes.len(), drawing.encoded_len());
+    assert_eq!(&bytes[..9], &[2, 4, 0, 0, 0, b'l', b'o', b'g', b'o']);
+    assert_eq!(Drawing::decode(&mut bytes.clone().freeze().into_buf()), Ok(drawing));
+
+    // 第1版的数据里没有color
+    let mut old = BytesMut::with_capacity(16);
+    old.put_u8(1);
+    "old".encode(&mut old);
+    Vec::<Shape>::new().encode(&mut old);
+    let decoded = Drawing::decode(&mut old.freeze().into_buf()).unwrap();
+    assert_eq!(decoded.color, Some("#000".to_owned()));
+
+    let newer = Drawing::decode(&mut (&[3u8][..]).into_buf());
+    assert_eq!(newer, Err(DecodeError::UnsupportedVersion { type_name: "Drawing", version: 3, supported: 2 }));
+    assert_eq!(Shape::decode(&mut (&[7u8][..]).into_buf()), Err(DecodeError::UnknownVariant { type_name: "Shape", tag: 7 }));
+
+    let mut truncated = (&bytes[..bytes.len() - 1]).into_buf();
+    assert!(matches!(Drawing::decode(&mut truncated), Err(DecodeError::UnexpectedEnd { .. })));
+    assert_eq!(truncated.remaining(), 0);
+
+    // `usize`按64位写出，32位平台上装不下时报错而不是截断
+    let large = 1u64 << 32;
+    let decoded = usize::decode(&mut large.to_bytes().freeze().into_buf());
+    if cfg!(target_pointer_width = "64") {
+        assert_eq!(decoded, Ok(large as usize));
+    } else {
+        assert_eq!(decoded, Err(DecodeError::Overflow { type_name: "usize", value: large.into() }));
+    }
+}
+
+///
+/// 上面的`hashmap!`只在测试函数里可见，每插入一个元素都可能重新分配，也不允许结尾的逗号。
+/// crate根上导出的`hashmap!`、`btreemap!`、`hashset!`、`btreeset!`和`vecdeque!`会先数出元素个数、
+/// 预先分配容量，可以互相嵌套；重复的字面量键交给过程宏`unique_keys!`在编译期检查，
+/// 比如`hashmap!{"a" => 1, "a" => 2}`会报错`Duplicate key "a", already given as entry 1`。
+///
+#[cfg_attr(test, test)]
+fn _09_01_16_collection_macros() {
+
+    use std::collections::{BTreeMap, HashMap};
+    use crate::{btreemap, btreeset, hashmap, hashset, vecdeque};
+
+    let counts = hashmap!{
+        'A' => 0,
+        'C' => 0,
+        'G' => 0,
+        'T' => 0,
+    };
+    assert_eq!(counts.len(), 4);
+    assert!(counts.capacity() >= 4);
+
+    let nested: BTreeMap<&str, HashMap<u8, Vec<i32>>> = btreemap!{
+        "odd" => hashmap!{1 => vec![1, 3], 2 => vec![5]},
+        "even" => hashmap!{},
+    };
+    assert_eq!(nested.keys().copied().collect::<Vec<_>>(), ["even", "odd"]);
+    assert_eq!(nested["odd"][&2], [5]);
+
+    // 只有字面量才能在编译期比较，变量之类的表达式不检查
+    let key = "a";
+    let set = hashset!{key, "a", "b"};
+    assert_eq!(set.len(), 2);
+
+    assert_eq!(btreeset![3, 1, 2].into_iter().collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(vecdeque![1, 1, 2,].iter().sum::<i32>(), 4);
+}
+
+include!(concat!(env!("OUT_DIR"), "/chap09_examples.rs"));
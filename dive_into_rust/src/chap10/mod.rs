@@ -21,7 +21,7 @@
 /// - 一般栈上分配的空间大小是编译阶段就可以确定的（C语言的VLA除外）；
 /// - 栈有一个确定的最大长度，超过这个长度会产生“栈溢出”(stack overflow)；
 /// - 堆的空间一般要更大一些，堆上的内存耗尽了，就会产生“内存分配不足”(out of memory)。
-#[test]
+#[cfg_attr(test, test)]
 fn _10_01_01_mem_manage() {
 
 
@@ -44,7 +44,7 @@ fn _10_01_01_mem_manage() {
 /// 开发效率和安全性也是可以接受的。
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _10_01_02_mem_segment_error() {
 
 }
@@ -64,7 +64,9 @@ fn _10_01_02_mem_segment_error() {
 ///
 /// 另外`panic!`不属于内存安全问题，锁机制也不属于内存安全问题
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _10_01_03_mem_safe() {
 
-}
\ No newline at end of file
+}
+
+include!(concat!(env!("OUT_DIR"), "/chap10_examples.rs"));
@@ -7,7 +7,7 @@
 /// - 当变量所在的作用域结束的时候，变量以及它代表的值将会被销毁。
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _11_01_01_ownership() {
 
     // 声明了变量s，并用String类型初始化，变量s就是该字符串的“所有者”
@@ -22,7 +22,7 @@ fn _11_01_01_ownership() {
 ///
 /// 一般把变量从出生到死亡的整个阶段，叫做一个变量的“生命周期”。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _11_01_02_ownership() {
 
     // 每个值只有一个所有者。变量s的生命周期从声明开始，到move给s1就结束了。
@@ -34,7 +34,7 @@ fn _11_01_02_ownership() {
 
 /// Rust不能做“赋值运算符重载”，需要“深复制”。
 /// 调用`clone`方法，需要对应类型impl标准库的`std::clone::Clone`
-#[test]
+#[cfg_attr(test, test)]
 fn _11_01_03_ownership() {
     let s = String::from("hello");
     let s1 = s.clone();
@@ -48,7 +48,7 @@ fn _11_01_03_ownership() {
 ///
 /// 移动语义是所有类型的默认语义。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _11_02_01_move() {
 
     fn create() -> String {
@@ -71,7 +71,7 @@ fn _11_02_01_move() {
 /// 普通变量绑定、函数传参、模式匹配场景下，凡是实现了`std::marker::Copy` trait的类型，都会执行copy语义
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _11_02_02_copy() {
 
     // 基本类型，比如数字、bool、字符等，都实现了`Copy` trait，因此具备copy语义
@@ -84,7 +84,7 @@ fn _11_02_02_copy() {
 /// 自定义类型，默认是没有 impl `Copy` trait的，
 ///
 /// 实现的方法有两种：impl和编译器扩展
-#[test]
+#[cfg_attr(test, test)]
 fn _11_02_03_copy_impl() {
 
     struct Foo {
@@ -107,7 +107,7 @@ fn _11_02_03_copy_impl() {
 
 ///
 /// 采用编译器扩展derive attribute让编译器自动帮我们实现
-#[test]
+#[cfg_attr(test, test)]
 fn _11_02_04_copy_derive() {
 
     #[derive(Copy,Clone)]
@@ -124,7 +124,7 @@ fn _11_02_04_copy_derive() {
 ///
 /// `Box`类型是Rust中一种常用的指针类型。它代表“拥有所有权的指针”
 /// `Box`类型永远是move语义，不能是copy语义。原因很简单，Rust的copy语义是浅复制，对于`Box`这种类型而言，浅复制必定导致二次释放。
-#[test]
+#[cfg_attr(test, test)]
 fn _11_03_01_box() {
 
     struct T {
@@ -143,7 +143,7 @@ fn _11_03_01_box() {
 /// - 对于数组类型，如果它内部的元素类型是Copy，那么这个数组也是Copy类型。
 /// - 对于元组tuple类型，如果它的每一个元素都是Copy类型，那么这个tuple也是Copy类型。
 /// - struct和enum类型不会自动实现Copy trait。只有当struct和enum内部的每个元素都是Copy类型时，编译器才允许我们针对此类型实现Copy trait。
-#[test]
+#[cfg_attr(test, test)]
 fn _11_04_01_copy_vs_clone() {
 	// 我们可以认为，Rust中只有POD(C++语言中的Plain Old Data)类型才能有资格实现`Copy` trait.
 	// 在Rust中，如果一个类型只包含POD数据类型的成员，并且没有自定义析构函数，那它就是POD类型。
@@ -159,7 +159,7 @@ fn _11_04_01_copy_vs_clone() {
 /// `clone`方法一般用于“基于语义的复制”操作。跟具体类型息息相关。
 /// 对于`Box`类型、`clone`执行的是“深复制”；
 /// 对于`Rc`类型，`clone`做的是把引用计数值加1.
-#[test]
+#[cfg_attr(test, test)]
 fn _11_04_02_clone() {
 }
 
@@ -169,7 +169,7 @@ fn _11_04_02_clone() {
 /// 通过derive`方式自动实现`Copy`和手动实现`Copy`有微小区别。当类型具有泛型参数的时候，比如`struct MyStruct <T> {}`，通过
 /// derive自动生成的代码会自动添加一个`T: Copy`的约束。
 /// 目前，只有一部分固定的特殊trait可以通过derive来自动实现。
-#[test]
+#[cfg_attr(test, test)]
 fn _11_04_03_derive() {
 }
 
@@ -180,7 +180,9 @@ fn _11_04_03_derive() {
 /// - `Copy` trait不是想实现就能实现的，它对类型是有要求的，有些类型不可能impl Copy。`Clone` trait没有什么前提条件，任何类型都可以实现(`unsized`类型除外，因为无法使用`unsized`类型作为返回值)；
 /// - `Copy` trait规定了这个类型在执行变量绑定、函数参数传递、函数返回等场景下的操作方式。即这个类型在这种场景下，必然执行的是“简单内存复制”，它由编译器控制。`Clone` trait里面的clone方法则由程序员自己控制；
 /// - 如果不想自定义`Clone` trait`的操作，可以使用编译器扩展，在类型上加上`#[derive (Clone)]`，让编译器帮我们自动添加；
-#[test]
+#[cfg_attr(test, test)]
 fn _11_04_04_diff() {
 
 }
+
+include!(concat!(env!("OUT_DIR"), "/chap11_examples.rs"));
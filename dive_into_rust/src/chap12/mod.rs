@@ -7,7 +7,7 @@
 ///
 /// Rust中编写“销毁器”的办法是impl `std::ops::Drop`
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _12_01_01_destructor() {
     use std::ops::Drop;
 
@@ -39,7 +39,7 @@ fn _12_01_01_destructor() {
 
 ///
 /// 资源管理
-#[test]
+#[cfg_attr(test, test)]
 fn _12_02_01_source_management() {
 
     use std::fs::File;
@@ -69,7 +69,7 @@ fn _12_02_01_source_management() {
 /// 实际上，
 /// `std::mem::drop`函数是最简单的函数，它是一个空方法实现。和move所有权到`_`的操作是等同的
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _12_03_01_initiative_destructure() {
 
     // v的生命周期开始
@@ -107,7 +107,7 @@ fn _12_03_01_initiative_destructure() {
 /// 想要实现`Copy` trait，类型必须满足一定条件。这个条件就是：如果一个类型可以使用memcpy的方式执行复制操作，且没有内存
 /// 安全问题，才能被允许实现`Copy` trait。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _12_04_01_drop_beyond_copy() {
     use std::ops::Drop;
 
@@ -125,7 +125,7 @@ fn _12_04_01_drop_beyond_copy() {
 ///
 /// 销毁标记并不是我们需要关心的问题，它通过环境变量标记`export DROP=2`来控制销毁的调用顺序
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _12_04_02_destructor_mark() {
     use std::ops::Drop;
     use std::mem::drop;
@@ -153,34 +153,4 @@ fn _12_04_02_destructor_mark() {
     println!("main end");
 }
 
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
+include!(concat!(env!("OUT_DIR"), "/chap12_examples.rs"));
@@ -2,7 +2,7 @@
 ///
 /// 生命周期
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _13_01_01_lifetime() {
 
     // v 的生命周期开始
@@ -21,7 +21,7 @@ fn _13_01_01_lifetime() {
 /// 借用指针（borrow pointer）也可以称作“引用”（reference）。
 /// 借用指针与普通指针的内部数据是一模一样的，唯一的区别是语义层面上的。它的作用是告诉编译器，它对指向的这块内存区域没有所有权。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _13_01_02_borrow() {
 
     fn foo(v: &mut Vec<i32>) {
@@ -38,7 +38,7 @@ fn _13_01_02_borrow() {
 ///
 /// `&mut` 指针，和变量绑定
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _13_01_03_borrow() {
     let mut var = 0_i32;
     {
@@ -74,7 +74,7 @@ fn _13_01_03_borrow() {
 ///
 /// 解决悬空指针的办法是引入`lifetime specifier`，它是个特殊的annotation，和类型参数写法一致。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _13_02_01_borrow_rule() {
     // 这里的参数采用的“引用传递”,意味着实参本身并未丢失对内存的管理权
     fn borrow_semantics(v: &Vec<i32>) {
@@ -119,31 +119,31 @@ fn _13_02_01_borrow_rule() {
 /// 但是，有个例外，当参数为`self` `&self` `&mut self`等时，若使用小数点语法调用成员方法，
 /// 在函数调用这里不能显式写出借用运算符。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _13_02_02_borrow_rule() {
     // 创建一个可变的String 类型实例
     let mut x: String = "hello".into();
     // 调用`len(&self) -> usize`函数。`self`的类型是`&Self`
     // `x.len()`等同于`String::len(&x)`
-    assert_eq!(6, x.len());
+    assert_eq!(5, x.len());
     // 调用`fn push(&mut self, ch: char)`函数。`self`的类型是`&mut Self`，因此它有权对字符串做修改
     // `x.push('!')`等同于`String::push(&mut x, '!')`
     x.push('!');
-    assert_eq!(7, x.len());
+    assert_eq!(6, x.len());
 
     // 调用`fn into_bytes(self) -> Vec<u8>`函数。注意self类型，此处发生了所有权转移
     // `x.into_bytes()`等同于`String::into_bytes(x)`
     let v = x.into_bytes();
 
     // 再次调用`len()`，编译失败，因为此处已经超过了x的生命周期
-    // ILLEGAL: assert_eq!(7, x.len());
+    // ILLEGAL: assert_eq!(6, x.len());
 }
 
 
 ///
 /// 任何借用指针的存在，都会导致原来变量被“冻结(Frozen)”
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _13_02_03_borrow_rule() {
     let mut x = 1_i32;
     let p = &mut x;
@@ -165,7 +165,7 @@ fn _13_02_03_borrow_rule() {
 /// 另外，`'static`是一个特殊的生命周期，它代表的是这个程序从开始到结束的整个阶段，所以它比其他任何生命周期都长。
 /// 这意味着，任意一个生命周期`'a`都满足`'static: 'a`。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _13_03_01_lifetime_specifier() {
 
     struct T {
@@ -182,7 +182,7 @@ fn _13_03_01_lifetime_specifier() {
 }
 
 
-#[test]
+#[cfg_attr(test, test)]
 fn _13_03_02_lifetime_specifier() {
     struct T {
         member: i32,
@@ -200,7 +200,7 @@ fn _13_03_02_lifetime_specifier() {
 /// Rust的引用类型是支持“协变”的。
 /// 在编译期眼里，生命周期就是一个区间，生命周期参数就是一个普通的泛型参数，它可以被特化为某个具体的生命周期。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _13_03_03_lifetime_specifier() {
     fn select<'a>(arg1: &'a i32, arg2: &'a i32) -> &'a i32 {
         if *arg1 > *arg2 {
@@ -218,7 +218,7 @@ fn _13_03_03_lifetime_specifier() {
 ///
 /// 类型的生命周期标记
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _13_04_01_type_lifetime_specifier() {
     struct Test<'a> {
         member: &'a str,
@@ -242,7 +242,7 @@ fn _13_04_01_type_lifetime_specifier() {
 /// - 如果有多个输入参数带生命周期参数，但其中有`&self`，`&mut self`那么返回值的生命周期被指定为这个参数；
 /// - 以上都不满足，就不能自动补全返回值的生命周期参数。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _13_04_02_type_lifetime_specifier() {
 
     fn get_str(s: &String) -> &str {
@@ -256,4 +256,6 @@ fn _13_04_02_type_lifetime_specifier() {
         "hello world"
     }
 
-}
\ No newline at end of file
+}
+
+include!(concat!(env!("OUT_DIR"), "/chap13_examples.rs"));
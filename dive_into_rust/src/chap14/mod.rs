@@ -15,7 +15,7 @@
 ///
 /// 编译错误示例
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _14_01_01_error_case() {
     let i = 0;
     let p1 = &i;                   // i被冻结
@@ -29,7 +29,7 @@ fn _14_01_01_error_case() {
 ///
 /// “共享不可变、可变不共享”
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _14_01_02_error_case() {
     let mut i = 0;
     let p1 = &i;                   // i被冻结，仍然可读，但不可写
@@ -42,7 +42,7 @@ fn _14_01_02_error_case() {
 ///
 /// 可变变量`i`被`&mut`借用了，所有权转移到了借用方，变量`i`被锁住了LOCKED(不能写，不能读)
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _14_01_04_error_case() {
     let mut i = 0;
     let p1 = &mut i;                // i被上锁
@@ -58,7 +58,7 @@ fn _14_01_04_error_case() {
 ///
 /// 内存不安全示例：枚举
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _14_02_01_unsafe_case() {
     use std::fmt::Debug;
     #[derive(Debug)]
@@ -77,7 +77,7 @@ fn _14_02_01_unsafe_case() {
 ///
 /// 内存不安全示例：迭代器
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _14_02_02_unsafe_case() {
     let mut arr = vec!["ABC", "DEF", "GHI"];
     for item in &arr {
@@ -88,7 +88,7 @@ fn _14_02_02_unsafe_case() {
 ///
 /// 内存不安全示例：悬空指针
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _14_02_03_unsafe_case() {
     let mut arr: Vec<i32> = vec![1, 2, 3, 4, 5];
     let p: &i32 = &arr[0];          // 出现悬空指针
@@ -96,3 +96,5 @@ fn _14_02_03_unsafe_case() {
         // ILLEGAL: arr.push(i);
     }
 }
+
+include!(concat!(env!("OUT_DIR"), "/chap14_examples.rs"));
@@ -16,7 +16,7 @@
 /// the fn body, stretching from the `let` until end of the enclosing scope.
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _15_01_01_nll() {
 
     fn foo() {
@@ -41,7 +41,7 @@ fn _15_01_01_nll() {
 /// variation of the previous example, where the `&mut data[..]` slice is not passed directly to
 /// `capitalize`, but is instead stored into a local variable.
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _15_01_01_nll_problem_case_variable_references() {
 
     fn bar() {
@@ -78,7 +78,7 @@ fn _15_01_01_nll_problem_case_variable_references() {
 /// arises around maps. Consider this function, which, given some `key`, processes the value found in
 /// `map[key]` if it exists, or else inserts a default value.
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _15_01_01_nll_problem_case_conditional_control_flow() {
 
     use std::collections::HashMap;
@@ -126,7 +126,7 @@ fn _15_01_01_nll_problem_case_conditional_control_flow() {
 /// conditional control flow across functions
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _15_01_01_nll_problem_case_conditional_control_flow_across_functions() {
 
     use std::collections::HashMap;
@@ -136,24 +136,24 @@ fn _15_01_01_nll_problem_case_conditional_control_flow_across_functions() {
     // 而且它的返回值也包含了一个引用，返回值的生命周期是和参数的生命周期一致的。
     // 这个方法的返回值会一直存在于整个match语句块中，所以编译器判定，针对map的引用也是
     // 一直存在于整个match语句块中。导致后面调用insert方法会发生冲突
-    fn get_default<K:Hash+Eq+Copy, V: Default>(map: &mut HashMap<K, V>,
-                                  key: K)
-                                  -> &mut V {
-        match map.get_mut(&key) { // -------------+ 'lifetime
-            Some(value) => value,              // |
-            None => {                          // |
-                map.insert(key, V::default()); // |
-                //  ^~~~~~ ERROR               // |
-                map.get_mut(&key).unwrap()     // |
-            }                                  // |
-        }                                      // |
-    }                                          // v
+    // ILLEGAL: fn get_default<K:Hash+Eq+Copy, V: Default>(map: &mut HashMap<K, V>,
+    // ILLEGAL:                               key: K)
+    // ILLEGAL:                               -> &mut V {
+    // ILLEGAL:     match map.get_mut(&key) { // -------------+ 'lifetime
+    // ILLEGAL:         Some(value) => value,              // |
+    // ILLEGAL:         None => {                          // |
+    // ILLEGAL:             map.insert(key, V::default()); // |
+    // ILLEGAL:             //  ^~~~~~ ERROR               // |
+    // ILLEGAL:             map.get_mut(&key).unwrap()     // |
+    // ILLEGAL:         }                                  // |
+    // ILLEGAL:     }                                      // |
+    // ILLEGAL: }                                          // v
 
     fn caller() {
         let mut map: HashMap<i32, u64> = HashMap::new();
         let key = 0_i32;
         {
-            let v = get_default(&mut map, key); // -+ 'lifetime
+            let v = get_default2(&mut map, key); // -+ 'lifetime
               // +-- get_default() -----------+ //  |
               // | match map.get_mut(&key) {  | //  |
               // |   Some(value) => value,    | //  |
@@ -174,17 +174,17 @@ fn _15_01_01_nll_problem_case_conditional_control_flow_across_functions() {
     // 在Some这个分支内存在一个引用，指向map的某个部分，而我们又把value返回了，
     // 这意味着编译器认为，这个借用从match开始一直到退出这个函数都存在
     // 因此后面的insert调用依然发生了冲突
-    fn get_default1<K:Hash+Eq+Copy, V: Default>(map: &mut HashMap<K, V>,
-                                   key: K)
-                                   -> &mut V {
-        match map.get_mut(&key) { // -------------+ 'lifetime
-            Some(value) => return value,       // |
-            None => { }                        // |
-        }                                      // |
-        map.insert(key, V::default());         // |
-        //  ^~~~~~ ERROR (still)                  |
-        map.get_mut(&key).unwrap()             // |
-    }                                          // v
+    // ILLEGAL: fn get_default1<K:Hash+Eq+Copy, V: Default>(map: &mut HashMap<K, V>,
+    // ILLEGAL:                                key: K)
+    // ILLEGAL:                                -> &mut V {
+    // ILLEGAL:     match map.get_mut(&key) { // -------------+ 'lifetime
+    // ILLEGAL:         Some(value) => return value,       // |
+    // ILLEGAL:         None => { }                        // |
+    // ILLEGAL:     }                                      // |
+    // ILLEGAL:     map.insert(key, V::default());         // |
+    // ILLEGAL:     //  ^~~~~~ ERROR (still)                  |
+    // ILLEGAL:     map.get_mut(&key).unwrap()             // |
+    // ILLEGAL: }                                          // v
 
 
     // 编译成功
@@ -222,17 +222,17 @@ fn _15_01_01_nll_problem_case_conditional_control_flow_across_functions() {
 
     // 让编译器能更准确地分析借用指针的生命周期，不要简单地与scope相绑定，
     // 更符合用户直观思维模式
-    #[feacture(nll)]
-    fn get_default4<K:Hash+Eq+Copy, V: Default>(map: &mut HashMap<K, V>,
-                                                key: K)
-                                                -> &mut V {
-     match map.get_mut(&key) {
-         Some(value) => process(value),
-         None => {
-             map.insert(key, V::default())
-         }
-    }
-    }
+    // NLL已经默认开启，不过这种情况仍然无法通过，要等到下一代借用检查器Polonius
+    // ILLEGAL: fn get_default4<K:Hash+Eq+Copy, V: Default>(map: &mut HashMap<K, V>,
+    // ILLEGAL:                                             key: K)
+    // ILLEGAL:                                             -> &mut V {
+    // ILLEGAL:  match map.get_mut(&key) {
+    // ILLEGAL:      Some(value) => process(value),
+    // ILLEGAL:      None => {
+    // ILLEGAL:          map.insert(key, V::default())
+    // ILLEGAL:      }
+    // ILLEGAL: }
+    // ILLEGAL: }
     {
         // NLL 的原理
         // 由于简单的使用 AST 分析最后使用的位置，会导致问题
@@ -246,4 +246,6 @@ fn _15_01_01_nll_problem_case_conditional_control_flow_across_functions() {
             // 它不会影响 RAII 语义
         }
     }
-}
\ No newline at end of file
+}
+
+include!(concat!(env!("OUT_DIR"), "/chap15_examples.rs"));
@@ -23,7 +23,7 @@
 /// `Cell`，
 /// 如果我们有共享引用指向一个对象，那么这个对象就不会被更改了。因为在共享引用存在的期间，不能有可变引用同时
 /// 指向它，因此它一定是不可变的。
-#[test]
+#[cfg_attr(test, test)]
 fn _16_01_01_interior_mutability() {
 
 
@@ -58,7 +58,7 @@ fn _16_01_01_interior_mutability() {
 /// 修改数据只能通过`Cell`来完成，用户无法创造一个直接指向数据的指针。
 ///
 ///
-/// ```
+/// ```ignore
 /// impl<T> Cell<T> {
 ///     pub fn get_mut(&mut self) -> &mut T {}
 ///     pub fn set(&self, val: T) {}
@@ -84,7 +84,7 @@ fn _16_01_01_interior_mutability() {
 /// - `get`方法接受的是`&self`参数，返回的是`T`类型，它可以在保留之前`Cell`类型不变的情况下返回一个新的
 /// `T`类型变量，因此它要求`T: Copy`约束。每次调用它的时候，都相当于把内部数据`memcpy`了一份返回出去。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _16_01_02_interior_mutability() {
     use std::cell::Cell;
 
@@ -105,7 +105,7 @@ fn _16_01_02_interior_mutability() {
 /// `RefCell`是另外一个提供了内部可变性的类型。它提供的方式与`Cell`类型有点不一样。`Cell`类型没办法制造出直接
 /// 指向内部数据的指针，而`RefCell`可以。
 ///
-/// ```
+/// ```ignore
 /// impl<T: ?Sized> RefCell<T> {
 ///     pub fn borrow(&self) -> Ref<T> {}
 ///     pub fn try_borrow(&self) -> Result<Ref<T>, BorrowError> {}
@@ -119,7 +119,7 @@ fn _16_01_02_interior_mutability() {
 /// 除此之外，`RefCell`最主要的两个方法就是`borrow`和`borrow_mut`，另外两个`try_borrow`和`try_borrow_mut`
 /// 只是它们俩的镜像版，区别仅在于错误处理的方式不同。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _16_01_03_interior_mutability() {
     use std::cell::RefCell;
 
@@ -156,7 +156,8 @@ fn _16_01_03_interior_mutability() {
 /// 结束的时候，会将这个值自动减1。同样，`borrow_mut`方法被调用的时候，它就记录一下当前存在“可变引用”。如果
 /// “共享引用”和“可变引用”同时出现了，就会报错。
 ///
-#[test]
+#[cfg_attr(test, test)]
+#[cfg_attr(test, should_panic)]
 fn _16_01_04_interior_mutability() {
     use std::cell::RefCell;
 
@@ -194,7 +195,9 @@ fn _16_01_04_interior_mutability() {
 /// 这个类型是唯一合法的将`&T`类型转为`&mut T`类型的办法。绝对不允许把`&T`直接转换为`&mut T`而获得可变性。
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _16_01_05_interior_mutability() {
 
 }
+
+include!(concat!(env!("OUT_DIR"), "/chap16_examples.rs"));
@@ -11,7 +11,7 @@
 ///
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _17_01_01_deref() {
 
     let v1 = 1;
@@ -25,7 +25,7 @@ fn _17_01_01_deref() {
 /// 解引用的操作可以被自定义。方法是，实现标准库中的`std::ops::Deref`或者`std::ops::DerefMut`这两个trait
 ///
 ///
-/// ```
+/// ```ignore
 ///pub trait Deref {
 ///  type Target: ?Sized;
 ///  fn deref(&self) -> &Self::Target;
@@ -37,14 +37,14 @@ fn _17_01_01_deref() {
 /// ```
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _17_01_02_deref() {
 }
 
 
 ///
 /// 自动解引用
-#[test]
+#[cfg_attr(test, test)]
 fn _17_01_02_auto_deref() {
 
     let s = "hello";
@@ -56,7 +56,7 @@ fn _17_01_02_auto_deref() {
 ///
 /// 自动解引用的用处
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _17_01_03_auto_deref() {
     use std::rc::Rc;
     use std::ops::Deref;
@@ -79,7 +79,7 @@ fn _17_01_03_auto_deref() {
 ///
 /// 有时候需要手动处理
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _17_01_04_handle_deref() {
     use std::rc::Rc;
     use std::ops::Deref;
@@ -94,7 +94,7 @@ fn _17_01_04_handle_deref() {
 ///
 /// 有时候需要手动处理
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _17_01_05_handle_deref() {
     use std::rc::Rc;
     use std::ops::Deref;
@@ -146,7 +146,7 @@ fn _17_01_05_handle_deref() {
 /// - `Rc`类型的引用计数是普通整数操作，只能用在单线程中；
 /// - `Arc`类型的引用计数是原子操作，可以用在多线程中。这一点是通过编译器静态检查保证的。
 ///
-/// ```
+/// ```ignore
 /// impl<T: ?Sized> Deref for Rc<T> {
 ///     type Target = T;
 ///
@@ -160,7 +160,7 @@ fn _17_01_05_handle_deref() {
 /// Rc类型重载了“解引用”运算符，恰好Target类型指定的是T。这就意味着编译器可以将`Rc<T>`类型在必要的时候自动
 /// 转换为`&T`类型，于是它就可以访问`T`的成员变量，调用`T`的成员方法了。因此，它可以被归类为“智能指针”。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _17_02_01_smart_pointer() {
 
     use std::rc::Rc;
@@ -187,7 +187,7 @@ fn _17_02_01_smart_pointer() {
 ///
 /// Rc智能指针内部实现了Clone和Drop，在clone方法，它没有对内部的数据实行深复制，而是将强引用计数值加1
 ///
-/// ```
+/// ```ignore
 /// impl<T: ?Szied> Clone for Rc<T> {
 ///     #[inline]
 ///     fn clone(&self) -> Rc<T> {
@@ -203,7 +203,7 @@ fn _17_02_01_smart_pointer() {
 /// 在drop方法中，也没有直接把内部数据释放掉，而是将强引用计数值减1，当强引用计数值减到0的时候，才会析构掉共享的
 /// 那块数据。当弱引用计数值也减为0的时候，才说明没有任何Rc/Weak指针指向这块内存，它占用的内存才会被彻底释放。
 ///
-/// ```
+/// ```ignore
 /// unsafe impl<#[may_dangle] T: ?Szied> Drop for Rc<T> {
 ///     fn drop(&mut self) {
 ///         unsafe {
@@ -229,7 +229,7 @@ fn _17_02_01_smart_pointer() {
 ///
 /// Rc的使用场景在于：当逻辑上不可变的方法的实现细节又要求某部分成员变量具有可变性的时候，我们可以使用“内部可变性”。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _17_02_02_smart_pointer() {
     use std::rc::Rc;
     use std::cell::RefCell;
@@ -254,7 +254,7 @@ fn _17_02_02_smart_pointer() {
 ///
 /// Cow在标准库中是一个enum：
 ///
-/// ```
+/// ```ignore
 /// pub enum Cow<'a, B: ?Sized + 'a> where B: ToOwned {
 ///     /// Borrowed data.
 ///     Borrowed(&'a B),
@@ -268,7 +268,7 @@ fn _17_02_02_smart_pointer() {
 /// 拥有所有权的对象，然后对这块拥有所有权的内存执行操作。
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _17_03_01_smart_pointer() {
 
     use std::borrow::Cow;
@@ -303,3 +303,5 @@ fn _17_03_01_smart_pointer() {
 // 更重要的是，在某些情况下，编译器帮我们插入了自动deref地调用，简化代码。
 
 // 在Deref的基础上，我们可以封装出一种自定义类型，它可以直接调用其内部的其它类型的成员方法，我们可以把这种类型称为智能指针类型。
+
+include!(concat!(env!("OUT_DIR"), "/chap17_examples.rs"));
@@ -11,7 +11,7 @@
 ///
 /// 内存泄露
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _18_01_01_mem_overflow() {
 
     use std::rc::Rc;
@@ -62,7 +62,7 @@ fn _18_01_01_mem_overflow() {
 ///
 /// 内存泄露不是在语言层面能彻底解决的问题。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _18_01_02_mem_safe() {
 
 }
@@ -74,23 +74,28 @@ fn _18_01_02_mem_safe() {
 /// 析构函数泄露是比内存泄露更严重的情况。因为析构函数是可以“自定义”的，析构函数里面
 /// 可能调用了“任意的”代码
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _18_01_03_mem_drop() {
-    // 以下示例目前无法编译通过，scoped已经被移除
     use std::thread;
 
+    // 早期的`thread::scoped`返回一个guard，在guard的析构函数中等待子线程结束。
+    // 但是析构函数不一定会被调用，比如`mem::forget(guard)`，子线程就可能访问已经释放的vec，
+    // 所以它已经被移除了：
+    // ILLEGAL: let guard = thread::scoped(move || { *x += 1; });
+    // ILLEGAL: guards.push(guard);
+    //
+    // 现在的`thread::scope`不依赖析构函数，作用域函数返回之前一定会等待所有子线程结束
     let mut vec = vec![0, 1, 2, 3, 4, 5, 6, 7];
-    {
-        let mut guards = Vec::new();
+    thread::scope(|scope| {
         for x in &mut vec {
-            let guard = thread::scoped(move || {
+            scope.spawn(move || {
                 *x += 1;
             });
-            guards.push(guard);
         }
-        // guards析构，在析构函数中等待子线程被销毁
-    }
+    });
     // 子线程已经全部退出
     println!("{:?}", vec);
+    assert_eq!(vec, [1, 2, 3, 4, 5, 6, 7, 8]);
 }
 
+include!(concat!(env!("OUT_DIR"), "/chap18_examples.rs"));
@@ -26,7 +26,7 @@
 ///
 /// Option为None时，尝试调用unwrap会触发panic
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _19_01_01_panic() {
     let x: Option<i32> = None;
     // PANIC: x.unwrap();
@@ -43,7 +43,7 @@ fn _19_01_01_panic() {
 /// - 某些高级抽象机制需要阻止栈展开，比如线程池。如果一个线程池出现了panic，我们希望只把这个线程关闭，而不至于将
 /// 整个线程池“拖下水”
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _19_01_02_panic() {
     use std::panic;
 
@@ -69,7 +69,7 @@ fn _19_01_02_panic() {
 /// 当我们在系统中使用了“异常”的时候，就一定要想清楚，每个组件应该提供那种层级的异常安全保证。在Rust中，
 /// 这个问题同样存在，但是一般叫做panic safety，于“异常”说的是同一件事情。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _19_01_03_panic_safety() {
     use std::panic;
     use std::panic::AssertUnwindSafe;
@@ -90,7 +90,7 @@ fn _19_01_03_panic_safety() {
 ///
 /// 多线程中，在某个线程中制造一个panic
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _19_01_04_panic_thread() {
 
     // 在thread2中，在达到某个条件的情况下会发生panic。这个panic是在Mutex锁定的状态下发生的。
@@ -135,3 +135,4 @@ fn _19_01_04_panic_thread() {
     println!("final value: {:?}", global);
 }
 
+include!(concat!(env!("OUT_DIR"), "/chap19_examples.rs"));
@@ -19,12 +19,10 @@
 //!
 //!
 
-use std::intrinsics::uninit;
-
 ///
 /// 比如，`String::from_raw_parts`就是一个unsafe函数，它的签名如下：
 ///
-/// ```
+/// ```ignore
 /// pub unsafe fn from_raw_parts(buf: *mut u8, length: usize, capacity: usize) -> String
 /// ```
 ///
@@ -40,13 +38,14 @@ use std::intrinsics::uninit;
 ///
 /// 当unsafe修饰一个trait的时候，那么意味着实现这个trait也需要使用unsafe，因为编译器是没有能力推理验证这个impl是否正确的。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _20_01_01_unsafe() {
-    let ptr: u8 = 0;
-    let len = 0;
-    let capacity = 1;
+    // 缓冲区必须是全局分配器分配的，长度和容量也要和分配时一致，空指针在运行时就会被检查出来
+    let mut buf = std::mem::ManuallyDrop::new(b"hello".to_vec());
+    let (ptr, len, capacity) = (buf.as_mut_ptr(), buf.len(), buf.capacity());
     // 自己保证这个缓冲区包含的是合法的utf-8字符串
-    let s = unsafe { String::from_raw_parts(ptr as *mut _, len, capacity) };
+    let s = unsafe { String::from_raw_parts(ptr, len, capacity) };
+    assert_eq!("hello", s);
 }
 
 
@@ -62,7 +61,7 @@ fn _20_01_01_unsafe() {
 /// - 不会执行任何自动化清理工作，比如自动释放内存等；
 /// - 裸指针赋值操作执行的是简单的内存浅复制，并且不存在borrow checker的限制。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _20_01_02_unsafe() {
 
     // 创建裸指针是完全安全的行为，只有对裸指针执行“解引用”才是不安全行为，必须在unsafe语句块中完成
@@ -70,7 +69,11 @@ fn _20_01_02_unsafe() {
     let x = 1_u32;
     let mut y: u32 = 1;
 
-    let raw_mut = &mut y as *mut u32 as *mut i32 as *mut i64;   // 这是安全的
+    let raw_mut = &mut y as *mut u32 as *mut i32;   // 这是安全的
+
+    // 裸指针之间可以随意转换，但是解引用的时候要自己保证类型的大小和对齐都是对的。
+    // 如果再转成`*mut i64`，写入的8个字节就超出了`y`的范围，这是未定义行为，编译器会直接拒绝：
+    // ILLEGAL: let raw_mut = &mut y as *mut u32 as *mut i32 as *mut i64;
 
     unsafe {
         *raw_mut = -1;      // 这是不安全的，必须在unsafe块中才能通过编译
@@ -83,7 +86,7 @@ fn _20_01_02_unsafe() {
 ///
 /// 在Rust中，&型引用、&mut型引用、Box指针，全部要求是合法的非空指针
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _20_01_03_unsafe() {
 
     fn raw_to_ref<'a>(p: *const i32) -> &'a i32 {
@@ -91,9 +94,14 @@ fn _20_01_03_unsafe() {
             &*p     // 把一个裸指针，转换为一个共享引用
         }
     }
-    let p : &i32 = raw_to_ref(std::ptr::null::<i32>());
+    let x = 1;
+    let p : &i32 = raw_to_ref(&x);
     println!("{}", p);
 
+    // 下面这样能通过编译，但它是未定义行为：debug模式下标准库检查到解引用了空指针，会直接终止整个进程，
+    // release模式下则什么都可能发生，所以这里不运行它
+    // let p : &i32 = raw_to_ref(std::ptr::null::<i32>());
+
 }
 
 ///
@@ -103,7 +111,7 @@ fn _20_01_03_unsafe() {
 ///
 /// 因此，大家不要滥用unsafe，能不写unsafe的情况，尽量不写
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _20_01_04_unsafe() {
     fn raw_to_ref<'a>(p: *const i32) -> Option<&'a i32> {
         if p.is_null() {
@@ -124,7 +132,7 @@ fn _20_01_04_unsafe() {
 /// 而不是像普通函数调用一样处理。另外，intrinsics是藏在一个`feature gate`后面的，这个feature可能永远不会稳定，
 /// 这些函数就不是准备直接提供给用户使用的。一般标准库会在这些函数基础上做一个更合适的封装给用户使用。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _20_01_05_intrinsics() {
 
     // `fn transmute<T，U>（e：T）->U` 函数可执行强制类型转换
@@ -147,7 +155,7 @@ fn _20_01_05_intrinsics() {
 /// 比如`copy`、`copy_nonoverlapping`、`write_bytes`、`move_val_init`、`volatile_load`等。
 /// 这些函数又在`std::ptr/std::mem`模块中做了个简单封装，然后暴露出来给用户使用。下面挑其中几个重要的函数介绍。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _20_01_06_intrinsics() {
 
     fn swap<T>(x: &mut T, y: &mut T) {
@@ -170,7 +178,7 @@ fn _20_01_06_intrinsics() {
 /// 虽然读者可以看出来，`&mut x[0..2]`和`&mut x[3..4]`根本就是指向两块独立的内存区域，它们同时存在是完全安全的。
 /// 但是编译器却觉得，`&mut x[A]`和`&mut x[B]`一定不能同时存在，否则就违反了alias+mutation的设计原则。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _20_01_07_split_borrow() {
     let mut x = [1_i32, 2, 3];
     {
@@ -181,7 +189,7 @@ fn _20_01_07_split_borrow() {
         third[0]  += 8;
         println!("{:?} {:?} {:?}", first, second, third);
     }
-    println!("{:?}", &x);
+    println!("{:?}", x);
 }
 
 ///
@@ -189,7 +197,7 @@ fn _20_01_07_split_borrow() {
 /// 协变
 /// Rust不支持普通泛型参数类型的协变和逆变，只对声明周期泛型参数存在协变和逆变
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _20_05_01_covariant() {
     type StrRef<'a> = &'a str;
 
@@ -216,7 +224,7 @@ fn _20_05_01_covariant() {
 /// Rust不支持普通泛型参数类型的协变和逆变，只对生命周期泛型参数存在协变和逆变。
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _20_05_02_convariant() {
 
     fn test1<'a>(s: &'a &'static str) {
@@ -267,7 +275,7 @@ fn _20_05_02_convariant() {
 /// 我们需要使用PhantomData来表达这个信息
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _20_05_03_phantom_data() {
     use std::fmt::Debug;
     use std::ptr::null;
@@ -296,10 +304,16 @@ fn _20_05_03_phantom_data() {
         }
     }
 
-    let mut r = R { x: null(), marker: PhantomData };
+    // local比r先析构，r的析构函数就会读到悬空指针，编译器能发现这个生命周期错误：
+    // ILLEGAL: let mut r = R { x: null(), marker: PhantomData };
+    // ILLEGAL: let local = S {  };
+    // ILLEGAL: r.ref_to(&local);
+    //                   ^^^^^^ 编译器获取到生命周期错误信息
+
+    // 先声明local，它就比r活得更久
     let local = S {  };
+    let mut r = R { x: null(), marker: PhantomData };
     r.ref_to(&local);
-    //       ^^^^^^ 编译器获取到生命周期错误信息
 }
 
 
@@ -336,7 +350,9 @@ fn _20_05_03_phantom_data() {
 ///
 ///unsafe是Rust的一块重要拼图，充分理解unsafe的意义和作用，才能让我们更好地理解safe的来源和可贵。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _20_06_01_undefined_behavior() {
 
 }
+
+include!(concat!(env!("OUT_DIR"), "/chap20_examples.rs"));
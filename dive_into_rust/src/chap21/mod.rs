@@ -16,7 +16,7 @@
 
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _21_01_01_vec() {
 
     let mut v1 = Vec::<i32>::new();
@@ -44,12 +44,13 @@ fn _21_01_01_vec() {
 /// 因为Vec事先了IntoIterator trait，标准库中的IntoIterator就是编译期留下来的一个扩展
 /// 内置for循环语法的接口。任何自定义类型，只要合理地实现了这个trait，就可以被用在内置的for循环里面。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _21_06_01_iter() {
 
     let mut x = vec![0_i32, 1, 2, 3, 4, 5];
 
-    for item in x {
+    // 写成`for item in x`会把x移动进循环，后面就不能再用了
+    for item in &x {
         println!("{}", item);
     }
 
@@ -62,4 +63,6 @@ fn _21_06_01_iter() {
     for i in x.iter() {
         println!("{}", i);
     }
-}
\ No newline at end of file
+}
+
+include!(concat!(env!("OUT_DIR"), "/chap21_examples.rs"));
@@ -4,12 +4,11 @@
 
 
 use std::fmt::Debug;
-use crate::chap22::ConvertTo;
 
 ///
 /// 泛型
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _22_01_01_generic() {
     // 泛型参数可以有多个也可以有默认值
     struct S<T=i32> {
@@ -25,13 +24,13 @@ fn _22_01_01_generic() {
 ///
 /// 泛型函数
 ///
-/// ```
+/// ```ignore
 /// fn contains<'a, P: Pattern<'a>>(&'a self, pat: P) -> bool
 /// ```
 ///
 /// 泛型参数满足Pattern trait的约束。意味着，所有实现了Pattern trait的类型，都可以作为参数使用。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _22_01_02_method() {
 
     fn compare_option<T1, T2>(first: Option<T1>, second: Option<T2>) -> bool {
@@ -61,7 +60,7 @@ fn _22_01_02_method() {
 
 /// impl块中的泛型
 ///
-/// ```
+/// ```ignore
 ///impl<T, U> Into<U> for T where U: From<T> {
 ///   fn into(self) -> U {
 ///       u::from(self)
@@ -69,7 +68,7 @@ fn _22_01_02_method() {
 ///}
 /// ```
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _22_01_03_impl_generic() {
 
 }
@@ -81,7 +80,7 @@ fn _22_01_03_impl_generic() {
 /// - 在泛型参数声明的时候使用冒号`:`指定；
 /// - 使用where子句指定；
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _22_01_04_generic_param_limit() {
     use std::cmp::PartialOrd;
 
@@ -92,7 +91,7 @@ fn _22_01_04_generic_param_limit() {
     fn max2<T> (a: T, b: T) -> T where T: PartialOrd { a }
 }
 
-#[test]
+#[cfg_attr(test, test)]
 fn _22_01_04_generic_limit_example() {
     use std::cmp::PartialOrd;
     use std::cmp::Ordering;
@@ -135,7 +134,7 @@ fn _22_01_04_generic_limit_example() {
 }
 
 /// 最直接的方式
-#[test]
+#[cfg_attr(test, test)]
 fn _22_01_04_generic_limit_example_derive() {
     use std::cmp::PartialOrd;
     use std::cmp::Ordering;
@@ -160,17 +159,89 @@ fn _22_01_04_generic_limit_example_derive() {
 }
 
 
+/// 只按部分字段比较时，`#[derive(OrderBy)]`一次生成`PartialEq`、`Eq`、`PartialOrd`和`Ord`：
+/// `#[order(by = "字段1, 字段2")]`按顺序列出参与比较的字段，没列出的字段被忽略，`reverse`把整体顺序反过来。
+/// 不写`by`的时候所有字段都参与比较，字段上可以用`#[order(ignore)]`排除，或者用`#[order(reverse)]`只反转这一个字段。
+/// 浮点数没有实现`Ord`，生成的代码用`total_cmp`比较，NaN也有确定的位置；浮点数的引用和`Option`也一样，`None`排在最前面。
+/// 其他包含浮点数的类型，比如`Vec<f64>`，会报编译错误。派生宏只看得到字面上的类型，浮点数的类型别名认不出来。
+#[cfg_attr(test, test)]
+fn _22_01_04_generic_limit_example_order_by() {
+    use dive_into_rust_derive::OrderBy;
+
+    fn max<T>(a: T, b: T) -> T where T: PartialOrd {
+        if a < b {
+            b
+        } else {
+            a
+        }
+    }
+
+    #[derive(OrderBy, Debug)]
+    #[order(by = "value")]
+    struct T {
+        value: i32,
+        label: &'static str,
+    }
+
+    let m = max(T { value: 1, label: "one" }, T { value: 2, label: "two" });
+    assert_eq!(m.label, "two");
+    assert_eq!(T { value: 1, label: "a" }, T { value: 1, label: "b" });
+
+    #[derive(OrderBy, Debug)]
+    #[order(by = "score, name", reverse)]
+    struct Player {
+        name: String,
+        score: f64,
+    }
+
+    let mut players = [
+        Player { name: "b".to_owned(), score: 2.5 },
+        Player { name: "a".to_owned(), score: 2.5 },
+        Player { name: "c".to_owned(), score: 9.0 },
+    ];
+    players.sort();
+    let names: Vec<&str> = players.iter().map(|player| player.name.as_str()).collect();
+    assert_eq!(names, ["c", "b", "a"]);
+
+    #[derive(OrderBy, Debug)]
+    struct Version {
+        major: u32,
+        #[order(reverse)]
+        minor: u32,
+        #[order(ignore)]
+        note: String,
+    }
+
+    assert!(Version { major: 1, minor: 9, note: String::new() } < Version { major: 1, minor: 2, note: String::new() });
+
+    #[derive(OrderBy, Debug)]
+    struct Reading<'a> {
+        value: Option<f64>,
+        limit: &'a f32,
+    }
+
+    let limit = 1.0;
+    let mut readings = [
+        Reading { value: Some(f64::NAN), limit: &limit },
+        Reading { value: Some(0.5), limit: &limit },
+        Reading { value: None, limit: &limit },
+    ];
+    readings.sort();
+    let values: Vec<String> = readings.iter().map(|reading| format!("{:?}", reading.value)).collect();
+    assert_eq!(values, ["None", "Some(0.5)", "Some(NaN)"]);
+}
+
 ///
 /// 关联类型(类型投影)
 ///
-/// ```
+/// ```ignore
 /// pub trait Iterator {
 ///    type Item;
 ///    ...
 ///}
 ///```
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _22_01_05_associate_type() {
     use std::iter::Iterator;
     use std::fmt::Debug;
@@ -185,7 +256,7 @@ fn _22_01_05_associate_type() {
     use_iter(v.iter());
 }
 
-#[test]
+#[cfg_attr(test, test)]
 fn _22_01_05_associate_type1() {
     use std::iter::Iterator;
     use std::fmt::Debug;
@@ -203,7 +274,7 @@ fn _22_01_05_associate_type1() {
 ///
 /// Rust中不支持泛型参数的重载，所以在调用时需要指定或者给类型实现Pattern trait
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _22_01_06_generic_override() {
     trait ConvertTo<T> {
         fn convert(&self) -> T;
@@ -232,7 +303,7 @@ fn _22_01_06_generic_override() {
 ///
 /// 目前泛型特化的完整规则依然处于酝酿之中，功能尚不稳定
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _22_01_07_cross_impl() {
 
     trait Foo {}
@@ -241,6 +312,8 @@ fn _22_01_07_cross_impl() {
 
     // 第一个 impl
     impl<T> Foo for T where T: B {}
-    // 第二个 impl
-    impl<T> Foo for T where T: C {}
+    // 第二个 impl：如果某个类型同时实现了B和C，两个impl就会冲突，所以编译器直接拒绝
+    // ILLEGAL: impl<T> Foo for T where T: C {}
 }
+
+include!(concat!(env!("OUT_DIR"), "/chap22_examples.rs"));
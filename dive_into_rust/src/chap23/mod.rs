@@ -2,21 +2,21 @@
 /// 闭包
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _23_01_01_closure() {
     let add = |a: i32, b: i32| -> i32 { return a + b; };
     let x = add(1, 2);
     println!("result is {}", x);
 }
 
-#[test]
+#[cfg_attr(test, test)]
 fn _23_01_02_closure() {
     let add = |a, b| -> i32 { return a + b; };
     let x = add(1, 2);
     println!("result is {}", x);
 }
 
-#[test]
+#[cfg_attr(test, test)]
 fn _23_01_03_closure() {
     let add = |a, b| a + b;
     let x = add(1, 2);
@@ -30,7 +30,7 @@ fn _23_01_03_closure() {
 /// - 如果一个外部变量在闭包中，通过`&mut`指针使用过，那么这个变量就需要使用`&mut`的方式捕获；
 /// - 如果一个外部变量在闭包中，通过所有权转移的方式使用过，那么这个变量就需要使用"by value"self的方式捕获。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _23_01_04_closure() {
 
     let x = 1_i32;
@@ -39,7 +39,7 @@ fn _23_01_04_closure() {
     println!("result is {}", x2);
 }
 
-#[test]
+#[cfg_attr(test, test)]
 fn _23_01_05_closure() {
     struct T(i32);
 
@@ -70,7 +70,7 @@ fn _23_01_05_closure() {
 ///
 /// move 关键字
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _23_02_01_move() {
 
     fn make_adder(x: i32) -> Box<Fn(i32) -> i32> {
@@ -99,7 +99,7 @@ fn _23_02_01_move() {
 /// FnOnce 仅能被调用一次
 /// Fn
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _23_02_02_move() {
     let v: Vec<i32> = vec![1, 2, 3];
     let c = move || for i in &v {println!("{}", i); };  // Fn可以被多次调用
@@ -111,7 +111,7 @@ fn _23_02_02_move() {
 /// 每个闭包，编译器都会为它生成一个匿名结构体类型；即使两个闭包的参数和返回值一致，
 /// 它们也是完全不同的两个类型，只是都实现了同一个trait而已。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _23_04_01_closure() {
     fn call_with_closure<F>(some_closure: F) -> i32 where F: Fn(i32) -> i32 {
         some_closure(1)
@@ -123,7 +123,7 @@ fn _23_04_01_closure() {
 ///
 /// 动态指派
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _23_04_02_dynamic_assign() {
     fn static_dispatch<F>(closure: &F) where F: Fn(i32) -> i32 {
         println!("static dispatch {}", closure(42));
@@ -159,7 +159,7 @@ fn _23_04_02_dynamic_assign() {
 /// 闭包生命周期
 ///
 /// 当使用闭包做参数或返回值的时候，生命周期会变得更加复杂。
-#[test]
+#[cfg_attr(test, test)]
 fn _23_04_03_closure_lifetime() {
 
     // “高阶生命周期”的表示方式
@@ -170,27 +170,4 @@ fn _23_04_03_closure_lifetime() {
     }
 }
 
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
+include!(concat!(env!("OUT_DIR"), "/chap23_examples.rs"));
@@ -14,7 +14,7 @@
 ///
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _24_01_01_dispatch() {
     trait Bird {
         fn fly(&self);
@@ -59,7 +59,7 @@ fn _24_01_01_dispatch() {
 ///
 /// 当指针指向trait的时候，这个指针就不是一个普通的指针了，变成了一个“胖指针”。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _23_01_01_trait_object() {
     use std::mem;
 
@@ -120,7 +120,7 @@ fn _23_01_01_trait_object() {
 /// object safe
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _23_02_01_object_safe() {
 
     // 如果给Self加上Sized约束
@@ -140,7 +140,7 @@ fn _23_02_01_object_safe() {
     // p.foo();
 }
 
-#[test]
+#[cfg_attr(test, test)]
 fn _23_02_02_object_safe() {
 
     trait Foo {
@@ -166,7 +166,7 @@ fn _23_02_02_object_safe() {
     // 如果我们针对foo2函数添加了`Self: Sized`约束，那么就不能通过trait object来调用这个函数
 }
 
-#[test]
+#[cfg_attr(test, test)]
 fn _23_02_03_object_safe() {
     trait Double {
         fn new() -> Self where Self: Sized;
@@ -187,7 +187,7 @@ fn _23_02_03_object_safe() {
 ///
 /// 为了解决闭包作为返回值的问题，aturon引入了impl trait方案
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _23_03_01_impl_trait() {
     fn foo(n: u32) -> impl Iterator<Item=u32> {
         (0..n).map(|x| x * 100)
@@ -210,8 +210,8 @@ fn _23_03_01_impl_trait() {
     // - 让`impl trait`用在函数参数中
     fn test(f: impl Fn(i32) -> i32) {}
 
-    // - 让`impl trait`用在类型别名中
-    type MyIter = impl Iterator<Item=i32>;
+    // - 让`impl trait`用在类型别名中，目前还需要nightly的`type_alias_impl_trait`
+    // ILLEGAL: type MyIter = impl Iterator<Item=i32>;
 
     // - 让`impl trait`用在trait中的方法参数或返回值中
     trait MyTrait {};
@@ -219,11 +219,13 @@ fn _23_03_01_impl_trait() {
         fn test() -> impl MyTrait;
     }
 
-    // - 让`impl Trait`用在trait中的关联类型中
-    trait Test1 {
-        type AT = impl MyTrait;
-    }
+    // - 让`impl Trait`用在trait中的关联类型中，这个设想至今也没有实现
+    // ILLEGAL: trait Test1 {
+    // ILLEGAL:     type AT = impl MyTrait;
+    // ILLEGAL: }
 
     // 某些场景下，`impl trait`这个语法具有明显的优势，因为它可以提高语言的表达能力。
     // 但不要过激地使用这个功能
 }
+
+include!(concat!(env!("OUT_DIR"), "/chap24_examples.rs"));
@@ -21,7 +21,7 @@ use std::collections::btree_map::BTreeMap;
 /// 用的就是usize类型》如果我们指定元素的类型是0大小的类型，那么，这个Vec根本不需要在堆上分配任何空间。
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _25_01_01_collections() {
 
     // 常见的几种构造Vec的方式
@@ -58,7 +58,7 @@ fn _25_01_01_collections() {
 
 /// 另外，因为Vec里面存在一个指向堆上的指针，它永远是非空的状态，编译器可以据此做优化，使得
 /// `size_of::<Option<Vec<T>>>() == size_of::<Vec<T>>()`。
-#[test]
+#[cfg_attr(test, test)]
 fn _23_01_02_collections() {
     struct ZeroSized {}
 
@@ -84,7 +84,7 @@ fn _23_01_02_collections() {
 /// VecDeque是一个双向队列。在它的头部或者尾部执行添加或者删除操作，都是效率很高的。它的用法和Vec非常相似，
 /// 主要是多了`pop_front()` `push_front()`等方法。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _23_02_01_collections() {
     use std::collections::VecDeque;
 
@@ -105,7 +105,7 @@ fn _23_02_01_collections() {
 ///
 /// HashMap的查找、插入、删除操作的平均时间复杂度都是O(1)。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _23_03_01_collections() {
     use std::collections::HashMap;
 
@@ -143,6 +143,41 @@ fn _23_03_01_collections() {
     println!("Find key: {}", book.contains_key(&p));
 }
 
+///
+/// 上面手写的`Person::new`可以用`#[derive(New)]`生成，参数按字段顺序排列，`String`字段接受任何`Into<String>`。
+///
+/// 字段多了以后，`#[derive(Builder)]`生成的构建器更好用：每个字段一个同名的setter，
+/// `build()`时检查必填字段，`Option`字段默认为`None`，`#[builder(default = ...)]`指定其它默认值。
+///
+#[cfg_attr(test, test)]
+fn _23_03_01_collections_new_builder() {
+    use std::collections::HashMap;
+    use dive_into_rust_derive::{Builder, New};
+
+    #[derive(New, Builder, Hash, Eq, PartialEq, Debug)]
+    struct Person {
+        first_name: String,
+        last_name: String,
+        nickname: Option<String>,
+        #[builder(default = 18)]
+        age: u32,
+    }
+
+    let mut book = HashMap::new();
+    book.insert(Person::new("John", "Smith", None, 30), "521-8976");
+    book.insert(Person::new("Sandra", "Dee", None, 30), "521-9655");
+
+    let p = Person::builder().first_name("John").last_name("Smith").age(30).build().unwrap();
+    assert_eq!(book.get(&p), Some(&"521-8976"));
+
+    let ted = Person::builder().last_name("Baker").first_name("Ted").nickname("T").build().unwrap();
+    assert_eq!((ted.nickname.as_deref(), ted.age), (Some("T"), 18));
+
+    let err = Person::builder().first_name("Ted").build().unwrap_err();
+    assert_eq!(err, PersonBuilderError { field: "last_name" });
+    assert_eq!(err.to_string(), "Missing required field `last_name` for Person");
+}
+
 ///
 ///
 /// HashMap里面，key存储的位置跟它本身的值密切相关，如果key本身变了，那么它存放的位置
@@ -150,7 +185,7 @@ fn _23_03_01_collections() {
 /// 修改它。但是，只读借用并不能完全保证它不被修改，读者应该能想到，只读借用依然可以改变具备
 /// 内部可变性特点的类型。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _23_03_02_collections() {
     use std::hash::{Hash, Hasher};
     use std::collections::HashMap;
@@ -195,7 +230,7 @@ fn _23_03_02_collections() {
 /// BTreeMap对key的要求是满足Ord约束，即具备“全序”特征。
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _23_03_03_collections() {
     use std::collections::BTreeMap;
 
@@ -236,7 +271,7 @@ fn _23_03_03_collections() {
 ///
 /// BTreeMap比HashMap多的一项功能是，它不仅可以查询单个key的结果，还可以查询一个区间的结果
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _25_03_04_collections() {
     use std::collections::BTreeMap;
 
@@ -254,7 +289,7 @@ fn _25_03_04_collections() {
 /// 迭代器
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _25_04_01_collections() {
     use std::iter::Iterator;
 
@@ -292,7 +327,7 @@ fn _25_04_01_collections() {
 /// producer + adapter + consumer
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _25_04_02_collections() {
     let v = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
     let mut iter = v.iter()
@@ -309,7 +344,7 @@ fn _25_04_02_collections() {
 ///
 /// for循环 ，它实际上是对IntoIterator trait的语法糖
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _25_05_01_collections() {
     use std::collections::HashMap;
     let v = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
@@ -322,4 +357,4 @@ fn _25_05_01_collections() {
     }
 }
 
-
+include!(concat!(env!("OUT_DIR"), "/chap25_examples.rs"));
@@ -0,0 +1,34 @@
+//!
+//! 依赖不稳定特性的生成器写法，只在开启`nightly` feature并使用nightly工具链时编译。
+//!
+//! 生成器现在改名叫coroutine，需要在闭包前加上`#[coroutine]`，`resume`也多了一个传入的参数，
+//! 这里是`()`。`yield`即使放在被cfg去掉的代码里也会报错，所以单独放在这个文件中。
+//!
+
+use std::ops::{Coroutine, CoroutineState};
+use std::pin::Pin;
+
+pub(super) fn fibonacci() {
+    let mut generator = #[coroutine] || {
+        let mut curr: u64 = 1;
+        let mut next: u64 = 1;
+        loop {
+            let new_next = curr.checked_add(next);
+            if let Some(new_next) = new_next {
+                curr = next;
+                next = new_next;
+                yield curr; // <-- 新的关键字
+            } else {
+                return;
+            }
+        }
+    };
+
+    loop {
+        // resume
+        match Pin::new(&mut generator).resume(()) {
+            CoroutineState::Yielded(v) => println!("{}", v),
+            CoroutineState::Complete(_) => return,
+        }
+    }
+}
@@ -12,7 +12,7 @@
 ///
 /// `AsRef`得到另外一个类型的共享引用，
 ///
-/// ```
+/// ```ignore
 /// pub trait AsRef<T: ?Sized> {
 ///     fn as_ref(&self) -> &T;
 /// }
@@ -20,7 +20,7 @@
 ///
 /// `AsMut`得到另外一个类型的可读写引用，
 ///
-/// ```
+/// ```ignore
 /// pub trait AsMut<T: ?Sized> {
 ///     fn as_mut(&mut self) -> &mut T;
 /// }
@@ -28,7 +28,7 @@
 ///
 /// 标准库中的String，针对了好几个类型实现了AsRef trait，
 ///
-/// ```
+/// ```ignore
 /// impl AsRef<str> for String
 /// impl AsRef<[u8]> for String
 /// impl AsRef<OsStr> for String
@@ -36,7 +36,7 @@
 /// ```
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _27_01_01_as() {
 
     fn iter_bytes<T: AsRef<[u8]>>(arg: T) {
@@ -57,7 +57,7 @@ fn _27_01_01_as() {
 ///
 /// `Borrow/BorrowMut`
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _27_01_02_borrow() {
 
 }
@@ -70,7 +70,7 @@ fn _27_01_02_borrow() {
 ///
 /// 如果存在`U：From<T>`，则实现`T：Into<U>`。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _27_01_03_from_into() {
     let s: &'static str = "hello";
     let str1: String = s.into();
@@ -86,7 +86,7 @@ fn _27_01_03_from_into() {
 /// ToOwned trait提供的是一种更“泛化”的Clone的功能。Clone一般是从&T类型变量创造一个新的T类型变量，
 /// 而ToOwned一般是从一个&T类型变量创造一个新的U类型变量。
 ///
-/// ```
+/// ```ignore
 /// pub enum Cow<'a, B>
 ///     where
 ///         B: 'a + ToOwned + ?Sized,
@@ -96,7 +96,7 @@ fn _27_01_03_from_into() {
 /// }
 /// ```
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _27_01_04_qto_owned() {
 
 }
@@ -105,7 +105,7 @@ fn _27_01_04_qto_owned() {
 ///
 /// 运算符重载
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _27_02_01_operator_override() {
     use std::ops::Add;
 
@@ -153,10 +153,49 @@ fn _27_02_01_operator_override() {
 
 }
 
+///
+/// 上面那样一个个手写`Add`很繁琐，`#[derive(Arithmetic)]`按字段逐个运算，一次生成
+/// `Add`、`Sub`、`Neg`以及`AddAssign`、`SubAssign`，左右两边是值还是引用的组合都覆盖了。
+/// 加上`#[arithmetic(scalar = i32)]`以后，还会生成每个字段都乘以(除以)同一个标量的`Mul`、`Div`。
+///
+#[cfg_attr(test, test)]
+fn _27_02_01_operator_override_derive() {
+    use dive_into_rust_derive::Arithmetic;
+
+    // 没有实现`Copy`，按值运算会把操作数move走，用引用运算则不会
+    #[derive(Arithmetic, Clone, Debug, PartialEq)]
+    #[arithmetic(scalar = i32)]
+    struct Complex {
+        real: i32,
+        imaginary: i32,
+    }
+
+    let c1 = Complex { real: 1, imaginary: 2 };
+    let c2 = Complex { real: 2, imaginary: 4 };
+    assert_eq!(&c1 + &c2, Complex { real: 3, imaginary: 6 });
+    assert_eq!(&c2 - &c1, c1);
+    assert_eq!(-c1.clone() + &c2, Complex { real: 1, imaginary: 2 });
+    assert_eq!(c2.clone() * 3 / 2, Complex { real: 3, imaginary: 6 });
+
+    let mut sum = c1.clone();
+    sum += c2;
+    sum -= &c1;
+    sum *= 2;
+    assert_eq!(sum, Complex { real: 4, imaginary: 8 });
+
+    #[derive(Arithmetic, Clone, Debug, PartialEq)]
+    #[arithmetic(scalar = f64)]
+    struct Vector<T>(T, T);
+
+    let v = Vector(1.5, -2.0);
+    assert_eq!(&v + &v, Vector(3.0, -4.0));
+    assert_eq!(-&v * 2.0, Vector(-3.0, 4.0));
+}
+
 ///
 /// Rust标准库实现了I/O处理
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _27_03_01_io() {
 
     use std::path::PathBuf;
@@ -174,7 +213,7 @@ fn _27_03_01_io() {
 ///
 /// 文件路径
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _27_03_02_path() {
     use std::io::prelude::*;
     use std::io::BufReader;
@@ -206,7 +245,7 @@ fn _27_03_02_path() {
 ///
 /// 标准输入输出
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _27_03_03_reader_writer() {
     use std::io::prelude::*;
     use std::io::BufReader;
@@ -241,7 +280,7 @@ fn _27_03_03_reader_writer() {
 /// 在Rust中，进程启动参数是调用独立的函数`std::env::args()`来得到的，或者使用`std::env::args_os()`来得到，
 /// 进程返回值也是调用独立函数`std::process::exit()`来指定。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _27_03_04_env() {
 
     if std::env::args().any(|arg| arg == "-kill") {
@@ -253,3 +292,4 @@ fn _27_03_04_env() {
 
 }
 
+include!(concat!(env!("OUT_DIR"), "/chap27_examples.rs"));
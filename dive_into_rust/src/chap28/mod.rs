@@ -7,7 +7,7 @@
 ///
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _28_01_01_thread() {
     use std::thread;
 
@@ -19,7 +19,7 @@ fn _28_01_01_thread() {
 ///
 /// 如果需要等待子线程执行结束，可以使用join方法
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _28_01_02_thread() {
     use std::thread;
     // child 的类型 `JoinHandle<T>`，这个T是闭包的返回类型
@@ -33,7 +33,7 @@ fn _28_01_02_thread() {
 ///
 /// 如果需要为子线程指定更多的参数信息，那么在创建的时候可以使用Builder模式
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _28_01_03_thread() {
     use std::thread;
 
@@ -51,7 +51,7 @@ fn _28_01_03_thread() {
 /// - `thread::park()` 暂停当前线程，进入等待状态。当`thread::Thread::unpark(&self)`方法被调用的时候，这个线程可以被恢复执行。
 /// - `thread::Thread::unpark(&self)` 恢复一个线程的执行。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _28_01_04_thread() {
     use std::thread;
     use std::time::Duration;
@@ -73,7 +73,7 @@ fn _28_01_04_thread() {
 ///
 /// 免数据竞争
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _28_03_01_data_race() {
     use std::thread;
     let mut health = 12;
@@ -90,7 +90,7 @@ fn _28_03_01_data_race() {
 /// - `std::marker::Sync`
 /// - `std::marker::Send`
 ///
-/// ```
+/// ```ignore
 /// pub fn spawn<F, T>(f: F) -> JoinHandle<T>
 ///     where F: FnOnce() -> T, F: Send + 'static, T: Send + 'static
 /// ```
@@ -98,7 +98,7 @@ fn _28_03_01_data_race() {
 /// 参数类型`F`有重要的约束条件`F:Send + 'static, T: Send + 'static`。但凡在线程间传递所有权都会发生安全问题的类型，
 /// 都无法在这个参数中出现，否则就是编译错误。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _28_04_01_sync() {
 
 }
@@ -109,7 +109,7 @@ fn _28_04_01_sync() {
 /// Arc是Rc的线程安全版本。全称“Atomic reference counter”。
 /// 它跟Rc最大的区别在于，引用计数用的是源自整数类型。Arc使用方法示例如下，
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _28_05_01_arc() {
     use std::sync::Arc;
     use std::thread;
@@ -137,7 +137,7 @@ fn _28_05_01_arc() {
 /// 我们需要调用`lock()`方法（或者try_lock）获得锁，然后才能对内部的数据进行读/写操作。
 /// 因为锁的存在，我们就可以保证整个“读/写”是一个完整的transaction。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _28_05_02_thread_shard() {
     use std::sync::Arc;
     use std::sync::Mutex;
@@ -178,7 +178,7 @@ fn _28_05_02_thread_shard() {
 /// - 同时允许多个读，最多只能有一个写；
 /// - 读和写不能同时存在；
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _28_06_01_rwlock() {
     use std::sync::Arc;
     use std::sync::RwLock;
@@ -215,7 +215,7 @@ fn _28_06_01_rwlock() {
 ///
 /// 它们都是符合Sync的，可以在多线程之间共享.
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _28_07_01_atomic() {
     use std::sync::Arc;
     use std::sync::atomic::{AtomicIsize, Ordering};
@@ -250,7 +250,7 @@ fn _28_07_01_atomic() {
 /// 而是使用了load把里面的值读取出来，然后执行加/减，操作完成后，再用store存储回去。编译程序我们看到，
 /// 是可以编译通过的。再执行，出现了问题：这次的执行结果就不是保证为0了。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _28_07_02_atomic() {
     use std::sync::Arc;
     use std::sync::atomic::{AtomicIsize, Ordering};
@@ -286,8 +286,10 @@ fn _28_07_02_atomic() {
 ///
 /// 死锁
 ///
+/// 这个例子真的会死锁，所以`cargo test`默认跳过它，可以用`dive run 28_08_01`观察，超时后会被杀掉。
 ///
-#[test]
+#[cfg_attr(test, test)]
+#[cfg_attr(test, ignore)]
 fn _28_08_01_deadlock() {
     use std::thread;
     use std::sync::{Mutex, Arc};
@@ -363,7 +365,7 @@ fn _28_08_01_deadlock() {
 /// 除了“锁”之外，Rust标准库还提供了一些其他线程之间的通信方式，比如Barrier等。Barrier是这样的一个类型，
 /// 它使用一个整数做初始化，可以使得多个线程在某个点上一起等待，然后再继续执行。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _28_09_01_barrier() {
     use std::sync::{Arc, Barrier};
     use std::thread;
@@ -392,7 +394,7 @@ fn _28_09_01_barrier() {
 /// `Condvar`
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _28_10_01_condvar() {
     use std::sync::{Arc, Mutex, Condvar};
     use std::thread;
@@ -424,7 +426,7 @@ fn _28_10_01_condvar() {
 
 ///
 /// 线程局部（Thread Local）的意思是，声明的这个变量看起来是一个变量，但它实际上在每一个线程中分别有自己独立的存储地址，是不同的变量，互不干扰。
-#[test]
+#[cfg_attr(test, test)]
 fn _28_11_01_thread_local() {
     use std::cell::RefCell;
     use std::thread;
@@ -453,12 +455,4 @@ fn _28_11_01_thread_local() {
     });
 }
 
-
-
-
-
-
-
-
-
-
+include!(concat!(env!("OUT_DIR"), "/chap28_examples.rs"));
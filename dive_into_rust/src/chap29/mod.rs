@@ -12,7 +12,7 @@
 ///
 ///
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _29_01_01_pipe() {
     use std::thread;
     use std::sync::mpsc::channel;
@@ -33,7 +33,7 @@ fn _29_01_01_pipe() {
 ///
 /// 多发送端，单接收端
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _29_01_02_pipe() {
     use std::thread;
     use std::sync::mpsc::channel;
@@ -61,7 +61,7 @@ fn _29_01_02_pipe() {
 ///
 /// 缓冲区的长度可以在建立管道的时候设置，而且0是有效数值。
 ///
-#[test]
+#[cfg_attr(test, test)]
 fn _29_02_01_sync_pipe() {
     use std::thread;
     use std::sync::mpsc::sync_channel;
@@ -76,4 +76,6 @@ fn _29_02_01_sync_pipe() {
 
     println!("receive first {}", rx.recv().unwrap());
     println!("receive second {}", rx.recv().unwrap());
-}
\ No newline at end of file
+}
+
+include!(concat!(env!("OUT_DIR"), "/chap29_examples.rs"));
@@ -0,0 +1,307 @@
+//!
+//! 基于`bytes`的紧凑二进制编码，配合`#[derive(Encode, Decode)]`使用。
+//!
+//! - 整数、浮点数一律小端序，`usize`/`isize`按64位写出；
+//! - `String`和`Vec`先写一个`u32`长度，再写内容；
+//! - `Option`先写一个字节的标记，枚举先写一个字节的变体序号；
+//! - 类型上标注了`#[codec(version = N)]`的，开头还有一个字节的版本号。
+//!
+
+use bytes::{Buf, BufMut, BytesMut};
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+
+/// 写入`BytesMut`。`encoded_len`用来预先分配空间。
+pub trait Encode {
+    fn encoded_len(&self) -> usize;
+
+    fn encode(&self, buf: &mut BytesMut);
+
+    fn to_bytes(&self) -> BytesMut {
+        let mut buf = BytesMut::with_capacity(self.encoded_len());
+        self.encode(&mut buf);
+        buf
+    }
+}
+
+/// 从任意`Buf`中读出，数据不完整或者不合法时返回`DecodeError`。
+pub trait Decode: Sized {
+    fn decode<B: Buf>(buf: &mut B) -> Result<Self, DecodeError>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    /// 还需要`needed`个字节，但只剩下`remaining`个
+    UnexpectedEnd { needed: usize, remaining: usize },
+    InvalidBool(u8),
+    InvalidChar(u32),
+    InvalidUtf8,
+    /// 按64位写出的`usize`/`isize`超出了当前平台的范围
+    Overflow { type_name: &'static str, value: i128 },
+    UnknownVariant { type_name: &'static str, tag: u8 },
+    /// 数据的版本比当前类型声明的版本还新
+    UnsupportedVersion { type_name: &'static str, version: u8, supported: u8 },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd { needed, remaining } => {
+                write!(f, "unexpected end of input: needed {} bytes, {} remaining", needed, remaining)
+            }
+            DecodeError::InvalidBool(byte) => write!(f, "invalid bool byte {}", byte),
+            DecodeError::InvalidChar(value) => write!(f, "invalid char {:#x}", value),
+            DecodeError::InvalidUtf8 => write!(f, "invalid UTF-8 in string"),
+            DecodeError::Overflow { type_name, value } => write!(f, "{} doesn't fit in {}", value, type_name),
+            DecodeError::UnknownVariant { type_name, tag } => {
+                write!(f, "unknown variant tag {} for {}", tag, type_name)
+            }
+            DecodeError::UnsupportedVersion { type_name, version, supported } => {
+                write!(f, "{} version {} is newer than the supported version {}", type_name, version, supported)
+            }
+        }
+    }
+}
+
+impl error::Error for DecodeError {}
+
+fn ensure<B: Buf>(buf: &B, needed: usize) -> Result<(), DecodeError> {
+    if buf.remaining() < needed {
+        return Err(DecodeError::UnexpectedEnd { needed, remaining: buf.remaining() });
+    }
+    Ok(())
+}
+
+macro_rules! codec_number {
+    ($($ty:ty => $put:ident, $get:ident;)*) => {
+        $(
+            impl Encode for $ty {
+                fn encoded_len(&self) -> usize {
+                    std::mem::size_of::<$ty>()
+                }
+
+                fn encode(&self, buf: &mut BytesMut) {
+                    buf.reserve(std::mem::size_of::<$ty>());
+                    buf.$put(*self);
+                }
+            }
+
+            impl Decode for $ty {
+                fn decode<B: Buf>(buf: &mut B) -> Result<Self, DecodeError> {
+                    ensure(buf, std::mem::size_of::<$ty>())?;
+                    Ok(buf.$get())
+                }
+            }
+        )*
+    };
+}
+
+codec_number! {
+    u8 => put_u8, get_u8;
+    i8 => put_i8, get_i8;
+    u16 => put_u16_le, get_u16_le;
+    i16 => put_i16_le, get_i16_le;
+    u32 => put_u32_le, get_u32_le;
+    i32 => put_i32_le, get_i32_le;
+    u64 => put_u64_le, get_u64_le;
+    i64 => put_i64_le, get_i64_le;
+    f32 => put_f32_le, get_f32_le;
+    f64 => put_f64_le, get_f64_le;
+}
+
+impl Encode for usize {
+    fn encoded_len(&self) -> usize {
+        8
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        (*self as u64).encode(buf);
+    }
+}
+
+impl Decode for usize {
+    fn decode<B: Buf>(buf: &mut B) -> Result<Self, DecodeError> {
+        let value = u64::decode(buf)?;
+        usize::try_from(value).map_err(|_| DecodeError::Overflow { type_name: "usize", value: value.into() })
+    }
+}
+
+impl Encode for isize {
+    fn encoded_len(&self) -> usize {
+        8
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        (*self as i64).encode(buf);
+    }
+}
+
+impl Decode for isize {
+    fn decode<B: Buf>(buf: &mut B) -> Result<Self, DecodeError> {
+        let value = i64::decode(buf)?;
+        isize::try_from(value).map_err(|_| DecodeError::Overflow { type_name: "isize", value: value.into() })
+    }
+}
+
+impl Encode for bool {
+    fn encoded_len(&self) -> usize {
+        1
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        (*self as u8).encode(buf);
+    }
+}
+
+impl Decode for bool {
+    fn decode<B: Buf>(buf: &mut B) -> Result<Self, DecodeError> {
+        match u8::decode(buf)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            byte => Err(DecodeError::InvalidBool(byte)),
+        }
+    }
+}
+
+impl Encode for char {
+    fn encoded_len(&self) -> usize {
+        4
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        (*self as u32).encode(buf);
+    }
+}
+
+impl Decode for char {
+    fn decode<B: Buf>(buf: &mut B) -> Result<Self, DecodeError> {
+        let value = u32::decode(buf)?;
+        std::char::from_u32(value).ok_or(DecodeError::InvalidChar(value))
+    }
+}
+
+/// 长度前缀，超过`u32`能表示的范围直接panic
+fn encode_len(len: usize, buf: &mut BytesMut) {
+    assert!(len <= u32::MAX as usize, "length {} doesn't fit the u32 prefix", len);
+    (len as u32).encode(buf);
+}
+
+impl Encode for str {
+    fn encoded_len(&self) -> usize {
+        4 + self.len()
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        encode_len(self.len(), buf);
+        buf.reserve(self.len());
+        buf.put_slice(self.as_bytes());
+    }
+}
+
+impl Encode for String {
+    fn encoded_len(&self) -> usize {
+        self.as_str().encoded_len()
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        self.as_str().encode(buf);
+    }
+}
+
+impl Decode for String {
+    fn decode<B: Buf>(buf: &mut B) -> Result<Self, DecodeError> {
+        let len = u32::decode(buf)? as usize;
+        ensure(buf, len)?;
+        let mut bytes = vec![0; len];
+        buf.copy_to_slice(&mut bytes);
+        String::from_utf8(bytes).map_err(|_| DecodeError::InvalidUtf8)
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encoded_len(&self) -> usize {
+        4 + self.iter().map(Encode::encoded_len).sum::<usize>()
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        encode_len(self.len(), buf);
+        for item in self {
+            item.encode(buf);
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encoded_len(&self) -> usize {
+        self.as_slice().encoded_len()
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        self.as_slice().encode(buf);
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode<B: Buf>(buf: &mut B) -> Result<Self, DecodeError> {
+        let len = u32::decode(buf)? as usize;
+        // 长度来自输入，不能直接拿来分配内存
+        let mut items = Vec::with_capacity(len.min(buf.remaining()));
+        for _ in 0..len {
+            items.push(T::decode(buf)?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encoded_len(&self) -> usize {
+        1 + self.as_ref().map_or(0, Encode::encoded_len)
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        match self {
+            Some(value) => {
+                1u8.encode(buf);
+                value.encode(buf);
+            }
+            None => 0u8.encode(buf),
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode<B: Buf>(buf: &mut B) -> Result<Self, DecodeError> {
+        match u8::decode(buf)? {
+            0 => Ok(None),
+            1 => T::decode(buf).map(Some),
+            tag => Err(DecodeError::UnknownVariant { type_name: "Option", tag }),
+        }
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encoded_len(&self) -> usize {
+        (**self).encoded_len()
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        (**self).encode(buf);
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for Box<T> {
+    fn encoded_len(&self) -> usize {
+        (**self).encoded_len()
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        (**self).encode(buf);
+    }
+}
+
+impl<T: Decode> Decode for Box<T> {
+    fn decode<B: Buf>(buf: &mut B) -> Result<Self, DecodeError> {
+        T::decode(buf).map(Box::new)
+    }
+}
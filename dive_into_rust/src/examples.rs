@@ -0,0 +1,121 @@
+//!
+//! 全书例子的注册表，`dive`命令行用它来列出和运行例子。
+//!
+//! 每一章的`EXAMPLES`由`build.rs`从标注了`#[cfg_attr(test, test)]`的函数生成，
+//! 所以这些函数在测试之外也会编译，`cargo test`照样把它们当作测试运行。
+//!
+
+pub struct Example {
+    /// 所在章节，也就是模块`chapNN`的编号
+    pub chapter: u32,
+    /// `dive run`用的编号，比如`_28_08_01_deadlock`的`28_08_01`。几个例子共用一个编号时，
+    /// 按出现的顺序加上后缀，比如`08_01_04a`、`08_01_04b`
+    pub id: &'static str,
+    /// 函数名，比如`_28_08_01_deadlock`
+    pub name: &'static str,
+    /// 文档注释的第一行非空内容，没有文档注释时为空
+    pub title: &'static str,
+    /// 标注了`#[should_panic]`，panic才是预期的结果
+    pub should_panic: bool,
+    pub run: fn(),
+}
+
+impl Example {
+    /// 标题，没有文档注释时用函数名里编号后面的部分
+    pub fn title(&self) -> &'static str {
+        if self.title.is_empty() {
+            let mut rest = self.name.trim_start_matches('_');
+            while let Some((part, tail)) = rest.split_once('_') {
+                if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                    break;
+                }
+                rest = tail;
+            }
+            rest
+        } else {
+            self.title
+        }
+    }
+}
+
+/// 所有编译进来的章节里的例子，按章节和在文件中出现的顺序排列
+pub fn all() -> Vec<&'static Example> {
+    let chapters: &[&'static [Example]] = &[
+        #[cfg(feature = "chap01")]
+        crate::chap01::EXAMPLES,
+        #[cfg(feature = "chap02")]
+        crate::chap02::EXAMPLES,
+        #[cfg(feature = "chap03")]
+        crate::chap03::EXAMPLES,
+        #[cfg(feature = "chap04")]
+        crate::chap04::EXAMPLES,
+        #[cfg(feature = "chap05")]
+        crate::chap05::EXAMPLES,
+        #[cfg(feature = "chap06")]
+        crate::chap06::EXAMPLES,
+        #[cfg(feature = "chap07")]
+        crate::chap07::EXAMPLES,
+        #[cfg(feature = "chap08")]
+        crate::chap08::EXAMPLES,
+        #[cfg(feature = "chap09")]
+        crate::chap09::EXAMPLES,
+        #[cfg(feature = "chap10")]
+        crate::chap10::EXAMPLES,
+        #[cfg(feature = "chap11")]
+        crate::chap11::EXAMPLES,
+        #[cfg(feature = "chap12")]
+        crate::chap12::EXAMPLES,
+        #[cfg(feature = "chap13")]
+        crate::chap13::EXAMPLES,
+        #[cfg(feature = "chap14")]
+        crate::chap14::EXAMPLES,
+        #[cfg(feature = "chap15")]
+        crate::chap15::EXAMPLES,
+        #[cfg(feature = "chap16")]
+        crate::chap16::EXAMPLES,
+        #[cfg(feature = "chap17")]
+        crate::chap17::EXAMPLES,
+        #[cfg(feature = "chap18")]
+        crate::chap18::EXAMPLES,
+        #[cfg(feature = "chap19")]
+        crate::chap19::EXAMPLES,
+        #[cfg(feature = "chap20")]
+        crate::chap20::EXAMPLES,
+        #[cfg(feature = "chap21")]
+        crate::chap21::EXAMPLES,
+        #[cfg(feature = "chap22")]
+        crate::chap22::EXAMPLES,
+        #[cfg(feature = "chap23")]
+        crate::chap23::EXAMPLES,
+        #[cfg(feature = "chap24")]
+        crate::chap24::EXAMPLES,
+        #[cfg(feature = "chap25")]
+        crate::chap25::EXAMPLES,
+        #[cfg(feature = "chap26")]
+        crate::chap26::EXAMPLES,
+        #[cfg(feature = "chap27")]
+        crate::chap27::EXAMPLES,
+        #[cfg(feature = "chap28")]
+        crate::chap28::EXAMPLES,
+        #[cfg(feature = "chap29")]
+        crate::chap29::EXAMPLES,
+    ];
+    chapters.iter().flat_map(|examples| examples.iter()).collect()
+}
+
+/// 按函数名或者编号查找
+pub fn find(query: &str) -> Option<&'static Example> {
+    let query = query.trim_start_matches('_');
+    all().into_iter().find(|example| example.name.trim_start_matches('_') == query || example.id == query)
+}
+
+/// 共用编号`number`、靠后缀区分的那些例子
+pub fn sharing(number: &str) -> Vec<&'static Example> {
+    let number = number.trim_start_matches('_');
+    all().into_iter()
+        .filter(|example| {
+            example.id.strip_prefix(number)
+                .is_some_and(|suffix| suffix.len() == 1 && suffix.bytes().all(|b| b.is_ascii_lowercase()))
+        })
+        .collect()
+}
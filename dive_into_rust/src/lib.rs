@@ -1,16 +1,115 @@
-#![feature(exclusive_range_pattern)]
-#![feature(core_intrinsics)]
-#![feature(never_type)]
+#![cfg_attr(nightly, feature(never_type))]
+#![cfg_attr(nightly, feature(core_intrinsics))]
+#![cfg_attr(nightly, allow(internal_features))]
+#![cfg_attr(nightly, feature(coroutines))]
+#![cfg_attr(nightly, feature(coroutine_trait))]
+#![cfg_attr(nightly, feature(stmt_expr_attributes))]
 #![deny(unused_extern_crates)]
 #![allow(unused_assignments)]
 #![allow(unused_imports)]
 #![warn(unreachable_code)]
-#![feature(maybe_uninit)]
-#![feature(dyn_trait)]
-#![feature(conservative_impl_trait)]
-#![feature(generators)]
-#![feature(generator_trait)]
 
-mod chap29;
+#[macro_use]
+mod macros;
 
-use crate::chap29::*;
+#[doc(hidden)]
+pub use dive_into_rust_derive::unique_keys as __unique_keys;
+
+pub mod codec;
+pub mod examples;
+pub mod trace;
+
+/// 每一章是一个可以单独关掉的feature。书里的例子有意写出各种不规范的代码来说明问题，
+/// 所以章节模块里关掉了这些代码触发的lint，只列出用到的，新写的例子照样要过lint检查。
+/// `codec`、`trace`这些给别人用的模块不受影响。
+macro_rules! chapters {
+    ($($feature:literal => $chapter:ident,)*) => {
+        $(
+            #[cfg(feature = $feature)]
+            #[allow(
+                unused,
+                bare_trait_objects,
+                dropping_copy_types,
+                function_casts_as_integer,
+                mismatched_lifetime_syntaxes,
+                static_mut_refs,
+            )]
+            #[allow(
+                clippy::assign_op_pattern,
+                clippy::bool_assert_comparison,
+                clippy::diverging_sub_expression,
+                clippy::doc_lazy_continuation,
+                clippy::empty_docs,
+                clippy::empty_line_after_doc_comments,
+                clippy::enum_variant_names,
+                clippy::eq_op,
+                clippy::explicit_auto_deref,
+                clippy::extra_unused_lifetimes,
+                clippy::get_first,
+                clippy::init_numbered_fields,
+                clippy::legacy_numeric_constants,
+                clippy::let_unit_value,
+                clippy::manual_unwrap_or_default,
+                clippy::match_like_matches_macro,
+                clippy::match_ref_pats,
+                clippy::match_single_binding,
+                clippy::missing_const_for_thread_local,
+                clippy::mutable_key_type,
+                clippy::needless_arbitrary_self_type,
+                clippy::needless_borrow,
+                clippy::needless_borrowed_reference,
+                clippy::needless_late_init,
+                clippy::needless_lifetimes,
+                clippy::needless_return,
+                clippy::never_loop,
+                clippy::non_canonical_clone_impl,
+                clippy::print_literal,
+                clippy::println_empty_string,
+                clippy::redundant_allocation,
+                clippy::single_match,
+                clippy::size_of_ref,
+                clippy::toplevel_ref_arg,
+                clippy::uninit_assumed_init,
+                clippy::unnecessary_literal_unwrap,
+                clippy::unused_enumerate_index,
+                clippy::useless_vec,
+                clippy::vec_init_then_push,
+                clippy::while_let_on_iterator,
+                clippy::zero_divided_by_zero,
+            )]
+            mod $chapter;
+        )*
+    };
+}
+
+chapters! {
+    "chap01" => chap01,
+    "chap02" => chap02,
+    "chap03" => chap03,
+    "chap04" => chap04,
+    "chap05" => chap05,
+    "chap06" => chap06,
+    "chap07" => chap07,
+    "chap08" => chap08,
+    "chap09" => chap09,
+    "chap10" => chap10,
+    "chap11" => chap11,
+    "chap12" => chap12,
+    "chap13" => chap13,
+    "chap14" => chap14,
+    "chap15" => chap15,
+    "chap16" => chap16,
+    "chap17" => chap17,
+    "chap18" => chap18,
+    "chap19" => chap19,
+    "chap20" => chap20,
+    "chap21" => chap21,
+    "chap22" => chap22,
+    "chap23" => chap23,
+    "chap24" => chap24,
+    "chap25" => chap25,
+    "chap26" => chap26,
+    "chap27" => chap27,
+    "chap28" => chap28,
+    "chap29" => chap29,
+}
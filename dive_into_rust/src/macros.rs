@@ -0,0 +1,73 @@
+//!
+//! 集合字面量宏：`hashmap!`、`btreemap!`、`hashset!`、`btreeset!`和`vecdeque!`。
+//!
+//! 能预先分配的集合按元素个数分配好容量；支持结尾的逗号，也可以互相嵌套。
+//! 键（集合的元素）如果是重复的字面量，比如写了两次`"a"`，会直接编译失败。
+//!
+
+/// 数出逗号分隔的表达式个数，表达式本身不会被求值
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __count {
+    (@unit $item:expr) => { () };
+    ($($item:expr),*) => { <[()]>::len(&[$($crate::__count!(@unit $item)),*]) };
+}
+
+/// `hashmap!{"a" => 1, "b" => 2}`
+#[macro_export]
+macro_rules! hashmap {
+    ($($key:expr => $value:expr),* $(,)?) => {{
+        $crate::__unique_keys!($($key),*);
+        #[allow(unused_mut)]
+        let mut map = ::std::collections::HashMap::with_capacity($crate::__count!($($key),*));
+        $( map.insert($key, $value); )*
+        map
+    }};
+}
+
+/// `btreemap!{"a" => 1, "b" => 2}`，`BTreeMap`没有容量的概念
+#[macro_export]
+macro_rules! btreemap {
+    ($($key:expr => $value:expr),* $(,)?) => {{
+        $crate::__unique_keys!($($key),*);
+        #[allow(unused_mut)]
+        let mut map = ::std::collections::BTreeMap::new();
+        $( map.insert($key, $value); )*
+        map
+    }};
+}
+
+/// `hashset!{"a", "b"}`
+#[macro_export]
+macro_rules! hashset {
+    ($($item:expr),* $(,)?) => {{
+        $crate::__unique_keys!($($item),*);
+        #[allow(unused_mut)]
+        let mut set = ::std::collections::HashSet::with_capacity($crate::__count!($($item),*));
+        $( set.insert($item); )*
+        set
+    }};
+}
+
+/// `btreeset!{"a", "b"}`
+#[macro_export]
+macro_rules! btreeset {
+    ($($item:expr),* $(,)?) => {{
+        $crate::__unique_keys!($($item),*);
+        #[allow(unused_mut)]
+        let mut set = ::std::collections::BTreeSet::new();
+        $( set.insert($item); )*
+        set
+    }};
+}
+
+/// `vecdeque![1, 2, 3]`，和`vec!`一样允许重复的元素
+#[macro_export]
+macro_rules! vecdeque {
+    ($($item:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut deque = ::std::collections::VecDeque::with_capacity($crate::__count!($($item),*));
+        $( deque.push_back($item); )*
+        deque
+    }};
+}
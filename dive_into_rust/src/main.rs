@@ -1,4 +1,264 @@
-#[cfg(not(test))]
+//!
+//! `dive`：列出和运行书中的例子。
+//!
+//! ```text
+//! dive list [章节]
+//! dive run <编号或函数名>... [--timeout 秒]
+//! dive run-chapter <章节> [--timeout 秒]
+//! ```
+//!
+//! 每个例子在单独的子进程里运行，这样才能捕获它的输出，panic或者死锁（比如`_28_08_01_deadlock`）
+//! 也不会影响其他例子；超时的子进程会被杀掉。
+//!
+
+use std::env;
+use std::io::{self, Read, Write};
+use std::process::{self, Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use dive_into_rust::examples::{self, Example};
+
+const USAGE: &str = "\
+usage: dive list [chapter]
+       dive run <id or name>... [--timeout <seconds>]
+       dive run-chapter <chapter> [--timeout <seconds>]";
+
+/// 子进程用来运行单个例子的内部命令
+const RUN_ONE: &str = "__run-one";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 例子panic时子进程的退出码，也就是标准库的默认值
+const PANIC_EXIT_CODE: i32 = 101;
+/// 子进程这一侧出错时的退出码，比如找不到例子，和命令行用错时一样
+const USAGE_EXIT_CODE: i32 = 2;
+
+enum Status {
+    Passed,
+    Panicked,
+    TimedOut,
+    /// 子进程没能运行这个例子，不算例子本身失败
+    RunnerError,
+    /// 其他退出码，或者被信号终止（`None`），比如例子调用了`process::exit`或者`abort`
+    Exited(Option<i32>),
+}
+
+struct Outcome {
+    status: Status,
+    stdout: String,
+    stderr: String,
+    elapsed: Duration,
+}
+
+impl Outcome {
+    fn succeeded(&self, example: &Example) -> bool {
+        match self.status {
+            Status::Passed => !example.should_panic,
+            Status::Panicked => example.should_panic,
+            Status::TimedOut | Status::RunnerError | Status::Exited(_) => false,
+        }
+    }
+
+    fn describe(&self, example: &Example) -> String {
+        match (&self.status, example.should_panic) {
+            (Status::Passed, false) => "ok".to_owned(),
+            (Status::Passed, true) => "FAILED (expected a panic)".to_owned(),
+            (Status::Panicked, false) => "PANICKED".to_owned(),
+            (Status::Panicked, true) => "ok (panicked as expected)".to_owned(),
+            (Status::TimedOut, _) => "TIMED OUT".to_owned(),
+            (Status::RunnerError, _) => "ERROR (couldn't run the example)".to_owned(),
+            (Status::Exited(Some(code)), _) => format!("FAILED (exited with code {})", code),
+            (Status::Exited(None), _) => "FAILED (killed by a signal)".to_owned(),
+        }
+    }
+}
+
+fn parse_chapter(argument: &str) -> Option<u32> {
+    argument.trim_start_matches("chap").parse().ok()
+}
+
+/// 读完一个管道里的所有输出，放在单独的线程里，免得子进程因为管道写满而阻塞
+fn collect<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
+}
+
+fn wait(child: &mut Child, timeout: Duration) -> io::Result<Option<process::ExitStatus>> {
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if start.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn run(example: &Example, timeout: Duration) -> io::Result<Outcome> {
+    let start = Instant::now();
+    let mut child = Command::new(env::current_exe()?)
+        .args([RUN_ONE, example.name])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = collect(child.stdout.take());
+    let stderr = collect(child.stderr.take());
+
+    let status = match wait(&mut child, timeout)? {
+        Some(status) if status.success() => Status::Passed,
+        Some(status) => match status.code() {
+            Some(PANIC_EXIT_CODE) => Status::Panicked,
+            Some(USAGE_EXIT_CODE) => Status::RunnerError,
+            code => Status::Exited(code),
+        },
+        None => Status::TimedOut,
+    };
+    let elapsed = start.elapsed();
+
+    Ok(Outcome {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+        elapsed,
+    })
+}
+
+fn report(example: &Example, outcome: &Outcome) {
+    println!("{} {} ... {} ({:.1?})", example.id, example.title(), outcome.describe(example), outcome.elapsed);
+    for line in outcome.stdout.lines() {
+        println!("    | {}", line);
+    }
+    for line in outcome.stderr.lines() {
+        println!("    ! {}", line);
+    }
+}
+
+fn run_all(examples: &[&Example], timeout: Duration) -> io::Result<bool> {
+    let mut failed = 0;
+    let mut errors = 0;
+    for example in examples {
+        let outcome = run(example, timeout)?;
+        report(example, &outcome);
+        if let Status::RunnerError = outcome.status {
+            errors += 1;
+        } else if !outcome.succeeded(example) {
+            failed += 1;
+        }
+    }
+
+    if examples.len() > 1 {
+        println!();
+        print!("{} examples, {} succeeded, {} failed", examples.len(), examples.len() - failed - errors, failed);
+        if errors > 0 {
+            print!(", {} couldn't run", errors);
+        }
+        println!();
+    }
+    Ok(failed == 0 && errors == 0)
+}
+
+fn list(chapter: Option<u32>) {
+    for example in examples::all() {
+        if chapter.is_none_or(|chapter| chapter == example.chapter) {
+            println!("{:<10} {:<36} {}", example.id, example.name, example.title());
+        }
+    }
+}
+
+/// 子进程这一侧：直接运行例子，panic时以默认的`PANIC_EXIT_CODE`退出
+fn run_one(name: &str) -> ! {
+    match examples::all().into_iter().find(|example| example.name == name) {
+        Some(example) => {
+            (example.run)();
+            let _ = io::stdout().flush();
+            process::exit(0);
+        }
+        None => {
+            eprintln!("Could not locate example `{}`", name);
+            process::exit(USAGE_EXIT_CODE);
+        }
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    process::exit(USAGE_EXIT_CODE);
+}
+
 fn main() {
+    let mut arguments: Vec<String> = env::args().skip(1).collect();
+
+    let mut timeout = DEFAULT_TIMEOUT;
+    if let Some(position) = arguments.iter().position(|argument| argument == "--timeout") {
+        let seconds = arguments.get(position + 1).and_then(|seconds| seconds.parse::<f64>().ok());
+        match seconds {
+            Some(seconds) if seconds > 0.0 => timeout = Duration::from_secs_f64(seconds),
+            _ => usage_error("--timeout needs a positive number of seconds"),
+        }
+        arguments.drain(position..position + 2);
+    }
+
+    let (command, rest) = match arguments.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => usage_error("Missing command"),
+    };
+
+    let succeeded = match (command, rest) {
+        (RUN_ONE, [name]) => run_one(name),
+        ("list", []) => {
+            list(None);
+            Ok(true)
+        }
+        ("list", [chapter]) => match parse_chapter(chapter) {
+            Some(chapter) => {
+                list(Some(chapter));
+                Ok(true)
+            }
+            None => usage_error(&format!("Invalid chapter `{}`", chapter)),
+        },
+        ("run", queries) if !queries.is_empty() => {
+            let mut selected = Vec::new();
+            for query in queries {
+                match examples::find(query) {
+                    Some(example) => selected.push(example),
+                    None => {
+                        let sharing: Vec<&str> = examples::sharing(query).iter().map(|example| example.id).collect();
+                        if sharing.is_empty() {
+                            usage_error(&format!("Could not locate example `{}`, see `dive list`", query));
+                        }
+                        usage_error(&format!("`{}` is shared by several examples: {}", query, sharing.join(", ")));
+                    }
+                }
+            }
+            run_all(&selected, timeout)
+        }
+        ("run-chapter", [chapter]) => {
+            let chapter = parse_chapter(chapter).unwrap_or_else(|| usage_error(&format!("Invalid chapter `{}`", chapter)));
+            let selected: Vec<&Example> = examples::all().into_iter().filter(|example| example.chapter == chapter).collect();
+            if selected.is_empty() {
+                usage_error(&format!("Chapter {} has no examples, see `dive list`", chapter));
+            }
+            run_all(&selected, timeout)
+        }
+        _ => usage_error(&format!("Invalid arguments for `{}`", command)),
+    }
+    .unwrap_or_else(|err| {
+        eprintln!("dive: {}", err);
+        process::exit(1);
+    });
 
-}
\ No newline at end of file
+    if !succeeded {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,131 @@
+//!
+//! `#[trace]`生成的代码在运行时用到的部分。
+//!
+//! 被标注的函数在进入时记录参数，返回时记录返回值和耗时，嵌套调用按深度缩进。
+//! 事件交给当前线程的`TraceSink`处理，默认打印到stderr，可以用`set_sink`换掉。
+//!
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::io::{self, Write};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceEvent<'a> {
+    /// 进入函数，参数已经用`Debug`格式化好了
+    Enter { function: &'static str, depth: usize, arguments: &'a [(&'static str, String)] },
+    /// 正常返回，标注了`skip_return`时`result`为`None`
+    Exit { function: &'static str, depth: usize, result: Option<&'a str>, elapsed: Duration },
+    /// 函数panic了，栈展开经过这里
+    Unwind { function: &'static str, depth: usize, elapsed: Duration },
+}
+
+impl<'a> fmt::Display for TraceEvent<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TraceEvent::Enter { function, depth, arguments } => {
+                write!(f, "{:indent$}-> {}(", "", function, indent = depth * 2)?;
+                for (i, (name, value)) in arguments.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{} = {}", name, value)?;
+                }
+                f.write_str(")")
+            }
+            TraceEvent::Exit { function, depth, result, elapsed } => {
+                write!(f, "{:indent$}<- {}", "", function, indent = depth * 2)?;
+                if let Some(result) = result {
+                    write!(f, " = {}", result)?;
+                }
+                write!(f, " [{:?}]", elapsed)
+            }
+            TraceEvent::Unwind { function, depth, elapsed } => {
+                write!(f, "{:indent$}<- {} panicked [{:?}]", "", function, elapsed, indent = depth * 2)
+            }
+        }
+    }
+}
+
+/// 接收跟踪事件，闭包`Fn(&TraceEvent)`也可以直接当作sink
+pub trait TraceSink {
+    fn record(&self, event: &TraceEvent);
+}
+
+impl<F: Fn(&TraceEvent)> TraceSink for F {
+    fn record(&self, event: &TraceEvent) {
+        self(event)
+    }
+}
+
+/// 默认的sink，每个事件一行写到stderr
+pub struct Stderr;
+
+impl TraceSink for Stderr {
+    fn record(&self, event: &TraceEvent) {
+        let _ = writeln!(io::stderr(), "{}", event);
+    }
+}
+
+thread_local! {
+    static SINK: RefCell<Rc<dyn TraceSink>> = RefCell::new(Rc::new(Stderr));
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// 替换当前线程的sink，返回原来的，方便之后换回去
+pub fn set_sink<S: TraceSink + 'static>(sink: S) -> Rc<dyn TraceSink> {
+    SINK.with(|current| current.replace(Rc::new(sink)))
+}
+
+/// 把之前`set_sink`返回的sink装回去
+pub fn restore_sink(sink: Rc<dyn TraceSink>) {
+    SINK.with(|current| *current.borrow_mut() = sink);
+}
+
+fn record(event: &TraceEvent) {
+    // 先把sink取出来，这样sink里面再调用被跟踪的函数也不会重复借用
+    let sink = SINK.with(|current| current.borrow().clone());
+    sink.record(event);
+}
+
+/// 一次调用的跟踪状态，由`#[trace]`生成的代码创建。
+/// 正常返回时调用`exit`，没有调用`exit`就被drop说明函数panic了。
+pub struct Trace {
+    function: &'static str,
+    depth: usize,
+    start: Instant,
+    exited: bool,
+}
+
+impl Trace {
+    pub fn enter(function: &'static str, arguments: &[(&'static str, &dyn fmt::Debug)]) -> Trace {
+        let depth = DEPTH.with(|depth| depth.replace(depth.get() + 1));
+        let arguments: Vec<(&'static str, String)> = arguments.iter()
+            .map(|&(name, value)| (name, format!("{:?}", value)))
+            .collect();
+        record(&TraceEvent::Enter { function, depth, arguments: &arguments });
+
+        Trace { function, depth, start: Instant::now(), exited: false }
+    }
+
+    pub fn exit(mut self, result: Option<&dyn fmt::Debug>) {
+        self.exited = true;
+        let result = result.map(|result| format!("{:?}", result));
+        record(&TraceEvent::Exit {
+            function: self.function,
+            depth: self.depth,
+            result: result.as_deref(),
+            elapsed: self.start.elapsed(),
+        });
+    }
+}
+
+impl Drop for Trace {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(self.depth));
+        if !self.exited {
+            record(&TraceEvent::Unwind { function: self.function, depth: self.depth, elapsed: self.start.elapsed() });
+        }
+    }
+}
@@ -0,0 +1,5 @@
+# {{title}}
+{{#each tags}}[{{this}}]{{/each}}
+{{#each lines}}{{@index}}. {{this.name}}: {{this.price}}
+{{/each}}
+{{#each grid}}({{#each this}}{{this}},{{/each}}){{/each}}
@@ -0,0 +1,3 @@
+{{name}}{{#if admin}} (admin){{else}} (guest){{/if}}
+{{#with email}}<{{this}}>{{else}}no email{{/with}}
+{{#each members}}{{#if this.active}}+{{else}}-{{/if}}{{this.name}}{{#with this.nickname}} aka {{this}}{{/with}};{{/each}}
@@ -0,0 +1,2 @@
+{{name}} ({{age}}, {{level}}) lives in {{address.city}}, {{address.location.0}}/{{address.location.1}}
+{{#each scores}}{{@index}}={{this}} {{/each}}
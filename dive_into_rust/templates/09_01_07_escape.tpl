@@ -0,0 +1,3 @@
+<h1>{{title}}</h1>
+<p title="{{author}}">{{{body}}}</p>
+<ul>{{#each comments}}<li>{{this}}</li>{{/each}}</ul>
@@ -0,0 +1,3 @@
+{{> partials/09_01_09_header.tpl}}
+{{#each items}}{{> partials/09_01_09_item.tpl}}{{/each}}
+{{> partials/09_01_09_footer.tpl}}
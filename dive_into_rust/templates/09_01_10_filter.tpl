@@ -0,0 +1,3 @@
+{{name | trim | upper}} #{{id | pad_left(8, '0')}}
+{{summary | truncate(11)}}...
+{{#each tags}}{{this | lower | slug}} {{/each}}
@@ -0,0 +1 @@
+-- {{title}} --
\ No newline at end of file
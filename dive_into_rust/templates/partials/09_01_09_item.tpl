@@ -0,0 +1 @@
+{{@index}}: {{this}}
@@ -0,0 +1,8 @@
+//! 集合字面量宏在编译期报告的重复键错误，和`tests/ui`里每个例子旁边的`.stderr`对比。
+//! 用`TRYBUILD=overwrite`重新生成它们。
+
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
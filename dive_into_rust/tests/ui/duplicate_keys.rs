@@ -0,0 +1,18 @@
+use dive_into_rust::{btreeset, hashmap, hashset};
+
+fn main() {
+    // 同一个字符串写了两次
+    let _ = hashmap! { "a" => 1, "b" => 2, "a" => 3 };
+
+    // 整数字面量按数值比较，和进制、后缀无关
+    let _ = hashmap! { 0x10u8 => "hex", 16u8 => "decimal", 0o20 => "octal" };
+    let _ = hashset! { -1, 2, -0x1 };
+
+    // 由字面量组成的元组和数组逐个元素比较
+    let _ = hashset! { (1, 'x'), (2, 'x'), (0b1, 'x') };
+    let _ = btreeset! { [1, 2], [2, 1], [1, 0x2] };
+
+    // 不是字面量的键在编译期没法比较，不会报错
+    let key = "a";
+    let _ = hashmap! { key => 1, "a" => 2 };
+}
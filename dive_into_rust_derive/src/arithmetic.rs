@@ -0,0 +1,246 @@
+//! Code generation for `#[derive(Arithmetic)]`.
+
+use proc_macro2::TokenStream;
+use std::collections::HashSet;
+use syn::spanned::Spanned;
+
+use crate::types::mentions_any;
+
+/// Reads `#[arithmetic(scalar = f64)]`, which may be repeated for several scalar types.
+fn scalar_attribute(attrs: &[syn::Attribute]) -> syn::Result<Vec<syn::Type>> {
+    let mut scalars = Vec::new();
+
+    for attr in attrs {
+        if attr.path().is_ident("arithmetic") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("scalar") {
+                    scalars.push(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("Unknown arithmetic option, expected `scalar = <type>`"))
+                }
+            })?;
+        }
+    }
+
+    Ok(scalars)
+}
+
+struct Generator<'a> {
+    ast: &'a syn::DeriveInput,
+    members: Vec<(syn::Member, &'a syn::Type)>,
+    type_params: HashSet<String>,
+}
+
+impl<'a> Generator<'a> {
+    /// The type's generics, plus a `'__arithmetic` lifetime if an operand is `borrowed`, with
+    /// `bound(field type)` required of every field type that involves the type parameters.
+    /// Fields of borrowed operands are cloned, so then every field type must be `Clone`, concrete
+    /// ones included, which makes rustc report a missing impl at the field.
+    fn generics<F>(&self, borrowed: bool, bound: F) -> syn::Generics
+        where F: Fn(&syn::Type) -> Vec<syn::WherePredicate>
+    {
+        let mut generics = self.ast.generics.clone();
+        if borrowed {
+            generics.params.insert(0, syn::parse_quote! { '__arithmetic });
+        }
+        let where_clause = generics.make_where_clause();
+        for (_, ty) in &self.members {
+            if mentions_any(ty, &self.type_params) {
+                where_clause.predicates.extend(bound(ty));
+            }
+            if borrowed {
+                where_clause.predicates.push(syn::parse_quote_spanned! {ty.span()=> #ty: ::std::clone::Clone });
+            }
+        }
+        generics
+    }
+
+    /// `Self` either owned or borrowed for `'__arithmetic`.
+    fn operand(&self, by_ref: bool) -> TokenStream {
+        let name = &self.ast.ident;
+        let (_, ty_generics, _) = self.ast.generics.split_for_impl();
+        if by_ref {
+            quote! { &'__arithmetic #name #ty_generics }
+        } else {
+            quote! { #name #ty_generics }
+        }
+    }
+
+    /// A field of `value`, cloned out of it when `value` is a reference.
+    fn field(value: TokenStream, member: &syn::Member, by_ref: bool) -> TokenStream {
+        if by_ref {
+            quote! { ::std::clone::Clone::clone(&#value.#member) }
+        } else {
+            quote! { #value.#member }
+        }
+    }
+
+    /// `Add`/`Sub` for every owned/borrowed combination of operands, and the `*Assign` trait
+    /// for an owned or borrowed right-hand side.
+    fn binary(&self, op: &str, method: &str, symbol: TokenStream) -> TokenStream {
+        let name = &self.ast.ident;
+        let (_, ty_generics, _) = self.ast.generics.split_for_impl();
+        let op = format_ident!("{}", op);
+        let method = format_ident!("{}", method);
+        let assign_op = format_ident!("{}Assign", op);
+        let assign_method = format_ident!("{}_assign", method);
+        let mut impls = quote! {};
+
+        for &(lhs_ref, rhs_ref) in &[(false, false), (false, true), (true, false), (true, true)] {
+            let generics = self.generics(lhs_ref || rhs_ref, |ty| vec![syn::parse_quote! { #ty: ::std::ops::#op<Output = #ty> }]);
+            let (impl_generics, _, where_clause) = generics.split_for_impl();
+            let lhs = self.operand(lhs_ref);
+            let rhs = self.operand(rhs_ref);
+            let values = self.members.iter().map(|(member, _)| {
+                let left = Self::field(quote! { self }, member, lhs_ref);
+                let right = Self::field(quote! { other }, member, rhs_ref);
+                quote! { #member: #left #symbol #right }
+            });
+
+            impls.extend(quote! {
+                impl #impl_generics ::std::ops::#op<#rhs> for #lhs #where_clause {
+                    type Output = #name #ty_generics;
+
+                    fn #method(self, other: #rhs) -> #name #ty_generics {
+                        #name { #( #values ),* }
+                    }
+                }
+            });
+        }
+
+        for &rhs_ref in &[false, true] {
+            let generics = self.generics(rhs_ref, |ty| vec![syn::parse_quote! { #ty: ::std::ops::#assign_op }]);
+            let (impl_generics, _, where_clause) = generics.split_for_impl();
+            let lhs = self.operand(false);
+            let rhs = self.operand(rhs_ref);
+            let statements = self.members.iter().map(|(member, _)| {
+                let right = Self::field(quote! { other }, member, rhs_ref);
+                quote! { ::std::ops::#assign_op::#assign_method(&mut self.#member, #right); }
+            });
+
+            impls.extend(quote! {
+                impl #impl_generics ::std::ops::#assign_op<#rhs> for #lhs #where_clause {
+                    fn #assign_method(&mut self, other: #rhs) {
+                        #( #statements )*
+                    }
+                }
+            });
+        }
+
+        impls
+    }
+
+    fn neg(&self) -> TokenStream {
+        let name = &self.ast.ident;
+        let (_, ty_generics, _) = self.ast.generics.split_for_impl();
+        let mut impls = quote! {};
+
+        for &by_ref in &[false, true] {
+            let generics = self.generics(by_ref, |ty| vec![syn::parse_quote! { #ty: ::std::ops::Neg<Output = #ty> }]);
+            let (impl_generics, _, where_clause) = generics.split_for_impl();
+            let operand = self.operand(by_ref);
+            let values = self.members.iter().map(|(member, _)| {
+                let value = Self::field(quote! { self }, member, by_ref);
+                quote! { #member: -#value }
+            });
+
+            impls.extend(quote! {
+                impl #impl_generics ::std::ops::Neg for #operand #where_clause {
+                    type Output = #name #ty_generics;
+
+                    fn neg(self) -> #name #ty_generics {
+                        #name { #( #values ),* }
+                    }
+                }
+            });
+        }
+
+        impls
+    }
+
+    /// `Mul`/`Div` by a scalar applied to every field, for an owned or borrowed `Self`, and the
+    /// `*Assign` trait. The scalar is used once per field, so it should be `Copy`.
+    fn scalar(&self, scalar: &syn::Type, op: &str, method: &str, symbol: TokenStream) -> TokenStream {
+        let name = &self.ast.ident;
+        let (_, ty_generics, _) = self.ast.generics.split_for_impl();
+        let op = format_ident!("{}", op);
+        let method = format_ident!("{}", method);
+        let assign_op = format_ident!("{}Assign", op);
+        let assign_method = format_ident!("{}_assign", method);
+        let generic_scalar = mentions_any(scalar, &self.type_params);
+        let mut impls = quote! {};
+
+        for &by_ref in &[false, true] {
+            let mut generics = self.generics(by_ref, |ty| vec![syn::parse_quote! { #ty: ::std::ops::#op<#scalar, Output = #ty> }]);
+            if generic_scalar {
+                generics.make_where_clause().predicates.push(syn::parse_quote! { #scalar: ::std::marker::Copy });
+            }
+            let (impl_generics, _, where_clause) = generics.split_for_impl();
+            let operand = self.operand(by_ref);
+            let values = self.members.iter().map(|(member, _)| {
+                let value = Self::field(quote! { self }, member, by_ref);
+                quote! { #member: #value #symbol scalar }
+            });
+
+            impls.extend(quote! {
+                impl #impl_generics ::std::ops::#op<#scalar> for #operand #where_clause {
+                    type Output = #name #ty_generics;
+
+                    fn #method(self, scalar: #scalar) -> #name #ty_generics {
+                        #name { #( #values ),* }
+                    }
+                }
+            });
+        }
+
+        let mut generics = self.generics(false, |ty| vec![syn::parse_quote! { #ty: ::std::ops::#assign_op<#scalar> }]);
+        if generic_scalar {
+            generics.make_where_clause().predicates.push(syn::parse_quote! { #scalar: ::std::marker::Copy });
+        }
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let operand = self.operand(false);
+        let members = self.members.iter().map(|(member, _)| member);
+        impls.extend(quote! {
+            impl #impl_generics ::std::ops::#assign_op<#scalar> for #operand #where_clause {
+                fn #assign_method(&mut self, scalar: #scalar) {
+                    #( ::std::ops::#assign_op::#assign_method(&mut self.#members, scalar); )*
+                }
+            }
+        });
+
+        impls
+    }
+}
+
+pub fn derive(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &ast.data {
+        syn::Data::Struct(data) if !data.fields.is_empty() => &data.fields,
+        _ => return Err(syn::Error::new(ast.ident.span(), "Arithmetic needs a struct with fields")),
+    };
+    let scalars = scalar_attribute(&ast.attrs)?;
+
+    let generator = Generator {
+        ast,
+        members: fields.iter().enumerate()
+            .map(|(i, field)| {
+                let member = match &field.ident {
+                    Some(ident) => syn::Member::Named(ident.clone()),
+                    None => syn::Member::Unnamed(syn::Index::from(i)),
+                };
+                (member, &field.ty)
+            })
+            .collect(),
+        type_params: ast.generics.type_params().map(|param| param.ident.to_string()).collect(),
+    };
+
+    let mut impls = generator.binary("Add", "add", quote! { + });
+    impls.extend(generator.binary("Sub", "sub", quote! { - }));
+    impls.extend(generator.neg());
+    for scalar in &scalars {
+        impls.extend(generator.scalar(scalar, "Mul", "mul", quote! { * }));
+        impls.extend(generator.scalar(scalar, "Div", "div", quote! { / }));
+    }
+
+    Ok(impls)
+}
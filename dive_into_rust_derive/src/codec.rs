@@ -0,0 +1,279 @@
+//! Code generation for `#[derive(Encode)]` and `#[derive(Decode)]`. Like `Render`, the traits and
+//! `DecodeError` are named unqualified, so they must be in scope where the derive is used.
+
+use proc_macro2::{Span, TokenStream};
+use std::collections::HashSet;
+
+use crate::types::mentions_any;
+
+/// Settings from `#[codec(...)]` on a type or field.
+#[derive(Default)]
+struct Options {
+    /// `version = N` on the type: the encoding starts with this version byte.
+    version: Option<(u8, Span)>,
+    /// `since = N` on a field: data older than version `N` doesn't contain it.
+    since: Option<(u8, Span)>,
+    /// `default = expr` on a field: its value when decoding older data. `Default::default()`
+    /// otherwise.
+    default: Option<syn::Expr>,
+}
+
+fn codec_attribute(attrs: &[syn::Attribute], on_field: bool) -> syn::Result<Options> {
+    let mut options = Options::default();
+
+    for attr in attrs {
+        if attr.path().is_ident("codec") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("version") && !on_field {
+                    let lit: syn::LitInt = meta.value()?.parse()?;
+                    options.version = Some((lit.base10_parse()?, lit.span()));
+                } else if meta.path.is_ident("since") && on_field {
+                    let lit: syn::LitInt = meta.value()?.parse()?;
+                    options.since = Some((lit.base10_parse()?, lit.span()));
+                } else if meta.path.is_ident("default") && on_field {
+                    options.default = Some(meta.value()?.parse()?);
+                } else if on_field {
+                    return Err(meta.error("Unknown codec option, expected `since = N` or `default = ...`"));
+                } else {
+                    return Err(meta.error("Unknown codec option, expected `version = N`"));
+                }
+                Ok(())
+            })?;
+        }
+    }
+
+    Ok(options)
+}
+
+/// A field of a struct or variant, bound by name in the generated code.
+struct Field<'a> {
+    member: syn::Member,
+    binding: syn::Ident,
+    field: &'a syn::Field,
+    options: Options,
+}
+
+fn fields(fields: &syn::Fields, version: Option<u8>) -> syn::Result<Vec<Field<'_>>> {
+    fields.iter().enumerate()
+        .map(|(i, field)| {
+            let options = codec_attribute(&field.attrs, true)?;
+            if let Some((since, span)) = options.since {
+                match version {
+                    None => return Err(syn::Error::new(span, "`since` needs #[codec(version = N)] on the type")),
+                    Some(version) if since > version => {
+                        return Err(syn::Error::new(span, format!("`since` is newer than the type's version {}", version)));
+                    }
+                    _ => (),
+                }
+            } else if let Some(default) = &options.default {
+                return Err(syn::Error::new_spanned(default, "`default` only applies to fields with `since`"));
+            }
+
+            let (member, binding) = match &field.ident {
+                Some(ident) => (syn::Member::Named(ident.clone()), format_ident!("__codec_field_{}", ident)),
+                None => (syn::Member::Unnamed(syn::Index::from(i)), format_ident!("__codec_field_{}", i)),
+            };
+            Ok(Field { member, binding, field, options })
+        })
+        .collect()
+}
+
+/// The pattern binding every field, e.g. `{ a: __codec_field_a }` or `{ 0: __codec_field_0 }`.
+fn pattern(fields: &[Field]) -> TokenStream {
+    let members = fields.iter().map(|field| &field.member);
+    let bindings = fields.iter().map(|field| &field.binding);
+    quote! { { #( #members: #bindings ),* } }
+}
+
+/// Adds `bound` for every field type that involves the type parameters.
+fn bounded_generics(ast: &syn::DeriveInput, bound: TokenStream) -> syn::Generics {
+    let type_params: HashSet<String> = ast.generics.type_params().map(|param| param.ident.to_string()).collect();
+    let mut generics = ast.generics.clone();
+    let types: Vec<&syn::Type> = match &ast.data {
+        syn::Data::Struct(data) => data.fields.iter().map(|field| &field.ty).collect(),
+        syn::Data::Enum(data) => data.variants.iter().flat_map(|variant| &variant.fields).map(|field| &field.ty).collect(),
+        syn::Data::Union(_) => Vec::new(),
+    };
+    for ty in types {
+        if mentions_any(ty, &type_params) {
+            generics.make_where_clause().predicates.push(syn::parse_quote! { #ty: #bound });
+        }
+    }
+    generics
+}
+
+fn version(ast: &syn::DeriveInput) -> syn::Result<Option<u8>> {
+    Ok(codec_attribute(&ast.attrs, false)?.version.map(|(version, _)| version))
+}
+
+/// Enums are tagged with one byte, the variant's position.
+fn variant_tags(data: &syn::DataEnum, name: &syn::Ident) -> syn::Result<()> {
+    if data.variants.len() > 256 {
+        return Err(syn::Error::new(name.span(), "Encode supports at most 256 variants"));
+    }
+    Ok(())
+}
+
+pub fn derive_encode(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let version = version(ast)?;
+    let name = &ast.ident;
+
+    let (length, body) = match &ast.data {
+        syn::Data::Struct(data) => {
+            let fields = fields(&data.fields, version)?;
+            let pattern = pattern(&fields);
+            let bindings: Vec<&syn::Ident> = fields.iter().map(|field| &field.binding).collect();
+            (
+                quote! {
+                    let #name #pattern = self;
+                    0 #( + Encode::encoded_len(#bindings) )*
+                },
+                quote! {
+                    let #name #pattern = self;
+                    #( Encode::encode(#bindings, buf); )*
+                },
+            )
+        }
+        syn::Data::Enum(data) => {
+            variant_tags(data, name)?;
+            let mut length_arms = quote! {};
+            let mut encode_arms = quote! {};
+            for (tag, variant) in data.variants.iter().enumerate() {
+                let tag = tag as u8;
+                let fields = fields(&variant.fields, version)?;
+                let pattern = pattern(&fields);
+                let bindings: Vec<&syn::Ident> = fields.iter().map(|field| &field.binding).collect();
+                let variant = &variant.ident;
+                length_arms.extend(quote! {
+                    #name::#variant #pattern => 1 #( + Encode::encoded_len(#bindings) )*,
+                });
+                encode_arms.extend(quote! {
+                    #name::#variant #pattern => {
+                        Encode::encode(&#tag, buf);
+                        #( Encode::encode(#bindings, buf); )*
+                    }
+                });
+            }
+
+            if data.variants.is_empty() {
+                (quote! { match *self {} }, quote! { match *self {} })
+            } else {
+                (quote! { match self { #length_arms } }, quote! { match self { #encode_arms } })
+            }
+        }
+        syn::Data::Union(_) => return Err(syn::Error::new(name.span(), "Encode can't be derived for unions")),
+    };
+
+    let (version_length, version_encode) = match version {
+        Some(version) => (quote! { 1 + }, quote! { Encode::encode(&#version, buf); }),
+        None => (quote! {}, quote! {}),
+    };
+
+    let generics = bounded_generics(ast, quote! { Encode });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics Encode for #name #ty_generics #where_clause {
+            fn encoded_len(&self) -> usize {
+                #version_length { #length }
+            }
+
+            #[allow(unused_variables)]
+            fn encode(&self, buf: &mut ::bytes::BytesMut) {
+                #version_encode
+                #body
+            }
+        }
+    })
+}
+
+/// Decodes `fields` into their bindings. Fields added `since` a later version take their
+/// default when the data is older.
+fn decode_fields(fields: &[Field]) -> TokenStream {
+    fields.iter()
+        .map(|field| {
+            let binding = &field.binding;
+            let ty = &field.field.ty;
+            let decode = quote! { <#ty as Decode>::decode(buf)? };
+            match field.options.since {
+                Some((since, _)) => {
+                    let default = match &field.options.default {
+                        Some(default) => quote! { #default },
+                        None => quote! { ::std::default::Default::default() },
+                    };
+                    quote! {
+                        let #binding: #ty = if __codec_version >= #since { #decode } else { #default };
+                    }
+                }
+                None => quote! { let #binding = #decode; },
+            }
+        })
+        .collect()
+}
+
+pub fn derive_decode(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let version = version(ast)?;
+    let name = &ast.ident;
+    let type_name = name.to_string();
+
+    let body = match &ast.data {
+        syn::Data::Struct(data) => {
+            let fields = fields(&data.fields, version)?;
+            let decode = decode_fields(&fields);
+            let pattern = pattern(&fields);
+            quote! {
+                #decode
+                Ok(#name #pattern)
+            }
+        }
+        syn::Data::Enum(data) => {
+            variant_tags(data, name)?;
+            let mut arms = quote! {};
+            for (tag, variant) in data.variants.iter().enumerate() {
+                let tag = tag as u8;
+                let fields = fields(&variant.fields, version)?;
+                let decode = decode_fields(&fields);
+                let pattern = pattern(&fields);
+                let variant = &variant.ident;
+                arms.extend(quote! {
+                    #tag => {
+                        #decode
+                        Ok(#name::#variant #pattern)
+                    }
+                });
+            }
+            quote! {
+                match <u8 as Decode>::decode(buf)? {
+                    #arms
+                    tag => Err(DecodeError::UnknownVariant { type_name: #type_name, tag }),
+                }
+            }
+        }
+        syn::Data::Union(_) => return Err(syn::Error::new(name.span(), "Decode can't be derived for unions")),
+    };
+
+    let version_decode = match version {
+        Some(version) => quote! {
+            let __codec_version = <u8 as Decode>::decode(buf)?;
+            if __codec_version > #version {
+                return Err(DecodeError::UnsupportedVersion {
+                    type_name: #type_name,
+                    version: __codec_version,
+                    supported: #version,
+                });
+            }
+        },
+        None => quote! {},
+    };
+
+    let generics = bounded_generics(ast, quote! { Decode });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics Decode for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn decode<__B: ::bytes::Buf>(buf: &mut __B) -> Result<Self, DecodeError> {
+                #version_decode
+                #body
+            }
+        }
+    })
+}
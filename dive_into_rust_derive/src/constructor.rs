@@ -0,0 +1,272 @@
+//! Code generation for `#[derive(New)]`, `#[derive(Builder)]` and `#[derive(SmartDefault)]`.
+
+use proc_macro2::TokenStream;
+use std::collections::HashSet;
+use syn::spanned::Spanned;
+
+use crate::types::{mentions_any, option_type, type_name};
+
+fn is_string(ty: &syn::Type) -> bool {
+    type_name(ty).is_some_and(|name| name == "String") && !matches!(ty, syn::Type::Reference(_))
+}
+
+/// `String` parameters accept anything `Into<String>`, so callers can pass `&str` literals.
+fn parameter(ty: &syn::Type) -> (TokenStream, TokenStream) {
+    if is_string(ty) {
+        (quote! { impl Into<#ty> }, quote! { .into() })
+    } else {
+        (quote! { #ty }, quote! {})
+    }
+}
+
+fn struct_fields<'a>(ast: &'a syn::DeriveInput, derive: &str) -> syn::Result<&'a syn::Fields> {
+    match &ast.data {
+        syn::Data::Struct(data) => Ok(&data.fields),
+        _ => Err(syn::Error::new(ast.ident.span(), format!("{} can only be derived for structs", derive))),
+    }
+}
+
+/// `new()` taking every field in declaration order.
+pub fn derive_new(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let fields = struct_fields(ast, "New")?;
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let mut parameters = Vec::new();
+    let mut values = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let argument = match &field.ident {
+            Some(ident) => ident.clone(),
+            None => format_ident!("field{}", i),
+        };
+        let (ty, conversion) = parameter(&field.ty);
+        parameters.push(quote! { #argument: #ty });
+        values.push(quote! { #argument #conversion });
+    }
+
+    let construct = match fields {
+        syn::Fields::Named(named) => {
+            let members = named.named.iter().map(|field| &field.ident);
+            quote! { #name { #( #members: #values ),* } }
+        }
+        syn::Fields::Unnamed(_) => quote! { #name ( #( #values ),* ) },
+        syn::Fields::Unit => quote! { #name },
+    };
+
+    Ok(quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            #vis fn new(#( #parameters ),*) -> Self {
+                #construct
+            }
+        }
+    })
+}
+
+/// How `build()` fills in a field that was never set.
+enum Fallback {
+    /// Report the field as missing.
+    Required,
+    /// `Option` fields stay `None`.
+    None,
+    /// `#[builder(default)]` or `#[builder(default = expr)]`.
+    Default(TokenStream),
+}
+
+fn builder_attribute(field: &syn::Field) -> syn::Result<Fallback> {
+    let mut fallback = match option_type(&field.ty) {
+        Some(_) => Fallback::None,
+        None => Fallback::Required,
+    };
+
+    for attr in &field.attrs {
+        if attr.path().is_ident("builder") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    fallback = if meta.input.peek(syn::Token![=]) {
+                        let expr: syn::Expr = meta.value()?.parse()?;
+                        Fallback::Default(quote! { #expr })
+                    } else {
+                        Fallback::Default(quote! { ::std::default::Default::default() })
+                    };
+                    Ok(())
+                } else {
+                    Err(meta.error("Unknown builder option, expected `default` or `default = ...`"))
+                }
+            })?;
+        }
+    }
+
+    Ok(fallback)
+}
+
+/// `Type::builder()` returning a `TypeBuilder` with a setter per field. `build()` fails with
+/// `TypeBuilderError` when a required field wasn't set.
+pub fn derive_builder(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match struct_fields(ast, "Builder")? {
+        syn::Fields::Named(named) => &named.named,
+        _ => return Err(syn::Error::new(ast.ident.span(), "Builder needs a struct with named fields")),
+    };
+
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    let builder = format_ident!("{}Builder", name);
+    let error = format_ident!("{}BuilderError", name);
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let generics = &ast.generics;
+    let message = format!("Missing required field `{{}}` for {}", name);
+
+    let mut slots = quote! {};
+    let mut setters = quote! {};
+    let mut values = Vec::new();
+    let mut members = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let field_name = ident.to_string();
+        let fallback = builder_attribute(field)?;
+
+        // An `Option` field that falls back to `None` is set with its inner value
+        let (slot_ty, wrap) = match (&fallback, option_type(&field.ty)) {
+            (Fallback::None, Some(inner)) => (inner, quote! { Some(value) }),
+            _ => (&field.ty, quote! { value }),
+        };
+        let (parameter_ty, conversion) = parameter(slot_ty);
+
+        let field_ty = &field.ty;
+        slots.extend(quote! { #ident: Option<#field_ty>, });
+        setters.extend(quote! {
+            #vis fn #ident(mut self, #ident: #parameter_ty) -> Self {
+                let value = #ident #conversion;
+                self.#ident = Some(#wrap);
+                self
+            }
+        });
+        values.push(match fallback {
+            Fallback::Required => quote! { self.#ident.ok_or(#error { field: #field_name })? },
+            Fallback::None => quote! { self.#ident.unwrap_or(None) },
+            Fallback::Default(default) => quote! { self.#ident.unwrap_or_else(|| #default) },
+        });
+        members.push(ident);
+    }
+
+    Ok(quote! {
+        /// Collects the fields one setter at a time; see `build()`.
+        #vis struct #builder #generics #where_clause {
+            #slots
+        }
+
+        /// The error returned by `build()` when a required field wasn't set.
+        #[derive(Debug, Clone, PartialEq)]
+        #vis struct #error {
+            pub field: &'static str,
+        }
+
+        impl ::std::fmt::Display for #error {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                write!(f, #message, self.field)
+            }
+        }
+
+        impl ::std::error::Error for #error {}
+
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            #vis fn builder() -> #builder #ty_generics {
+                #builder { #( #members: None ),* }
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #builder #ty_generics #where_clause {
+            #setters
+
+            #vis fn build(self) -> Result<#name #ty_generics, #error> {
+                Ok(#name { #( #members: #values ),* })
+            }
+        }
+    })
+}
+
+/// Reads `#[default = literal]`, or `#[default(expr)]` for expressions that aren't
+/// literals, which rustc doesn't accept after `=`.
+fn default_attribute(field: &syn::Field) -> syn::Result<Option<syn::Expr>> {
+    match field.attrs.iter().find(|attr| attr.path().is_ident("default")) {
+        Some(attr) => match &attr.meta {
+            syn::Meta::NameValue(name_value) => Ok(Some(name_value.value.clone())),
+            syn::Meta::List(_) => attr.parse_args().map(Some),
+            syn::Meta::Path(path) => Err(syn::Error::new_spanned(
+                path,
+                "default expects a value, e.g. #[default = 5] or #[default(-1)]",
+            )),
+        },
+        None => Ok(None),
+    }
+}
+
+/// `Default` using each field's `#[default = expr]`, or its own `Default`, plus a
+/// `with_<field>` method per field for struct-update-style construction.
+pub fn derive_smart_default(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let fields = struct_fields(ast, "SmartDefault")?;
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    let type_params: HashSet<String> = ast.generics.type_params().map(|param| param.ident.to_string()).collect();
+    let mut generics = ast.generics.clone();
+
+    let mut values = Vec::new();
+    let mut setters = quote! {};
+    for (i, field) in fields.iter().enumerate() {
+        values.push(match default_attribute(field)? {
+            // `#[default = "text"]` works for `String` fields too
+            Some(expr) if is_string(&field.ty) => quote_spanned! {expr.span()=> ::std::convert::Into::into(#expr) },
+            Some(expr) => quote_spanned! {expr.span()=> #expr },
+            None => {
+                // Generic fields without an expression need their own `Default`
+                let ty = &field.ty;
+                if mentions_any(ty, &type_params) {
+                    generics.make_where_clause().predicates.push(syn::parse_quote! { #ty: ::std::default::Default });
+                }
+                quote_spanned! {ty.span()=> ::std::default::Default::default() }
+            }
+        });
+
+        let member = match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(syn::Index::from(i)),
+        };
+        let setter = match &field.ident {
+            Some(ident) => format_ident!("with_{}", ident),
+            None => format_ident!("with_{}", i),
+        };
+        let (ty, conversion) = parameter(&field.ty);
+        setters.extend(quote! {
+            #vis fn #setter(mut self, value: #ty) -> Self {
+                self.#member = value #conversion;
+                self
+            }
+        });
+    }
+
+    let construct = match fields {
+        syn::Fields::Named(named) => {
+            let members = named.named.iter().map(|field| &field.ident);
+            quote! { #name { #( #members: #values ),* } }
+        }
+        syn::Fields::Unnamed(_) => quote! { #name ( #( #values ),* ) },
+        syn::Fields::Unit => quote! { #name },
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::std::default::Default for #name #ty_generics #where_clause {
+            fn default() -> Self {
+                #construct
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            #setters
+        }
+    })
+}
@@ -0,0 +1,31 @@
+//! Helpers for error messages shared by the derives.
+
+/// The candidate closest to `name`, if it is close enough to be a likely typo.
+pub fn suggest<'b, I>(name: &str, candidates: I) -> Option<&'b str>
+where
+    I: IntoIterator<Item = &'b String>,
+{
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|&(distance, candidate)| distance <= ::std::cmp::max(1, candidate.len() / 3))
+        .min()
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == cb { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
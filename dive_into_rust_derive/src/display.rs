@@ -0,0 +1,283 @@
+//! Code generation for `#[derive(Display)]`.
+
+use proc_macro2::{Span, TokenStream};
+use std::collections::{HashMap, HashSet};
+
+use crate::diagnostics::suggest;
+use crate::types::{mentions_any, peel_references};
+
+pub fn derive(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let type_params: HashSet<String> = ast.generics.type_params().map(|param| param.ident.to_string()).collect();
+    let mut bounds = Vec::new();
+    let name = &ast.ident;
+
+    let body = match &ast.data {
+        syn::Data::Struct(data) => {
+            let format = display_attribute(&ast.attrs)?.ok_or_else(|| missing_attribute(&ast.ident))?;
+            let (pattern, write) = generate_write(&data.fields, &format, &type_params, &mut bounds)?;
+            quote! {
+                let #name #pattern = self;
+                #write
+            }
+        }
+        syn::Data::Enum(data) => {
+            let mut arms = quote! {};
+            for variant in &data.variants {
+                let variant_ident = &variant.ident;
+                let arm = match display_attribute(&variant.attrs)? {
+                    Some(format) => {
+                        let (pattern, write) = generate_write(&variant.fields, &format, &type_params, &mut bounds)?;
+                        quote! { #name::#variant_ident #pattern => { #write } }
+                    }
+                    // Unit variants without a format print their name
+                    None if variant.fields.is_empty() => {
+                        let text = variant_ident.to_string();
+                        quote! { #name::#variant_ident => f.write_str(#text), }
+                    }
+                    None => return Err(missing_attribute(variant_ident)),
+                };
+                arms.extend(arm);
+            }
+
+            if data.variants.is_empty() {
+                quote! { match *self {} }
+            } else {
+                quote! { match self { #arms } }
+            }
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new(ast.ident.span(), "Display can't be derived for unions"));
+        }
+    };
+
+    // Generic fields need whichever formatting trait their placeholder uses
+    let mut generics = ast.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for (ty, bound) in bounds {
+        where_clause.predicates.push(syn::parse_quote! { #ty: #bound });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+fn missing_attribute(ident: &syn::Ident) -> syn::Error {
+    syn::Error::new(
+        ident.span(),
+        format!("Could not find display attribute on `{}`, e.g. #[display(\"{{field1}} / {{field2}}\")]", ident),
+    )
+}
+
+/// Reads `#[display("...")]`.
+fn display_attribute(attrs: &[syn::Attribute]) -> syn::Result<Option<syn::LitStr>> {
+    match attrs.iter().find(|attr| attr.path().is_ident("display")) {
+        Some(attr) => attr.parse_args().map(Some),
+        None => Ok(None),
+    }
+}
+
+/// A `{field:spec}` placeholder.
+struct Placeholder {
+    /// Byte range of the field name inside the format string.
+    start: usize,
+    end: usize,
+    /// The formatting trait the spec selects, such as `Display` for `{x}` or `Debug` for `{x:?}`.
+    bound: syn::Path,
+    /// Byte ranges of the fields the spec takes its width or precision from, as in `{x:>width$}`.
+    counts: Vec<(usize, usize)>,
+}
+
+/// Finds the placeholders of a format string, skipping `{{` and `}}`.
+fn parse_placeholders(format: &str) -> Result<Vec<Placeholder>, String> {
+    let mut placeholders = Vec::new();
+    let mut chars = format.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().map(|&(_, next)| next) == Some('{') => {
+                chars.next();
+            }
+            '}' if chars.peek().map(|&(_, next)| next) == Some('}') => {
+                chars.next();
+            }
+            '}' => return Err("Unmatched `}` in format string, write `}}` for a literal brace".to_owned()),
+            '{' => {
+                let close = format[i..].find('}').ok_or("Unclosed `{` in format string")? + i;
+                let content = &format[i + 1..close];
+                let (argument, spec) = match content.find(':') {
+                    Some(colon) => (&content[..colon], &content[colon + 1..]),
+                    None => (content, ""),
+                };
+
+                let start = i + 1 + (argument.len() - argument.trim_start().len());
+                let end = start + argument.trim().len();
+                if start == end {
+                    return Err(format!("`{{{}}}` needs a field name, e.g. `{{field1}}`", content));
+                }
+                let spec_start = close - spec.len();
+                placeholders.push(Placeholder {
+                    start,
+                    end,
+                    bound: formatting_trait(spec)?,
+                    counts: count_arguments(spec)?.into_iter()
+                        .map(|(start, end)| (spec_start + start, spec_start + end))
+                        .collect(),
+                });
+
+                while chars.peek().is_some_and(|&(j, _)| j <= close) {
+                    chars.next();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok(placeholders)
+}
+
+/// Finds the `name$` arguments of a spec, which set the width or precision from another field.
+fn count_arguments(spec: &str) -> Result<Vec<(usize, usize)>, String> {
+    if spec.contains(".*") {
+        return Err("`.*` precision isn't supported, name the field instead, e.g. `{x:.prec$}`".to_owned());
+    }
+    // The fill character comes before the alignment and may itself be `$`
+    let skip = match spec.char_indices().nth(1) {
+        Some((j, '<' | '^' | '>')) => j,
+        _ => 0,
+    };
+
+    let mut counts = Vec::new();
+    for (j, c) in spec.char_indices().filter(|&(j, _)| j >= skip) {
+        if c != '$' {
+            continue;
+        }
+        let mut start = spec[skip..j].rfind(|c: char| !(c.is_alphanumeric() || c == '_')).map_or(skip, |k| skip + k + 1);
+        // A leading `0` is the zero-padding flag, as in `{x:0width$}` or `{x:01$}`
+        let name = &spec[start..j];
+        if name.starts_with(|c: char| c.is_ascii_digit()) && !name.chars().all(|c| c.is_ascii_digit()) {
+            start += name.len() - name.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        } else if name.len() > 1 && name.starts_with('0') && name.chars().all(|c| c.is_ascii_digit()) {
+            start += 1;
+        }
+        if start == j {
+            return Err(format!("`{}` needs a field name before `$`, e.g. `{{x:>width$}}`", spec));
+        }
+        counts.push((start, j));
+    }
+
+    Ok(counts)
+}
+
+fn formatting_trait(spec: &str) -> Result<syn::Path, String> {
+    let name = match spec.chars().last() {
+        Some('?') => "Debug",
+        Some('x') => "LowerHex",
+        Some('X') => "UpperHex",
+        Some('o') => "Octal",
+        Some('b') => "Binary",
+        Some('e') => "LowerExp",
+        Some('E') => "UpperExp",
+        Some(c) if c.is_alphabetic() => return Err(format!("Unknown format trait `{}`", c)),
+        _ => "Display",
+    };
+    let name = format_ident!("{}", name);
+    Ok(syn::parse_quote! { ::std::fmt::#name })
+}
+
+/// Generates the destructuring pattern for `fields` and the `write!` that formats them. Fields
+/// are passed as named arguments, `{0}` becoming `{__display_0}` for tuple fields, and
+/// `{x:>width$}` becoming `{__display_x:>0$}` with `*__display_width` as the first positional
+/// argument.
+fn generate_write(fields: &syn::Fields, format: &syn::LitStr, type_params: &HashSet<String>,
+                  bounds: &mut Vec<(syn::Type, syn::Path)>) -> syn::Result<(TokenStream, TokenStream)> {
+    let value = format.value();
+    let error = |message: String| syn::Error::new(format.span(), message);
+    let placeholders = parse_placeholders(&value).map_err(error)?;
+
+    let by_key: HashMap<String, (usize, &syn::Field)> = fields.iter().enumerate()
+        .map(|(i, field)| match &field.ident {
+            Some(ident) => (ident.to_string(), (i, field)),
+            None => (i.to_string(), (i, field)),
+        })
+        .collect();
+
+    let lookup = |key: &str| by_key.get_key_value(key).ok_or_else(|| {
+        let mut message = format!("Could not locate field `{}` in the display format", key);
+        if let Some(suggestion) = suggest(key, by_key.keys()) {
+            message.push_str(&format!("; did you mean `{}`?", suggestion));
+        }
+        error(message)
+    });
+
+    let mut rewritten = String::new();
+    let mut position = 0;
+    // Fields the pattern binds, and the ones among them that are formatted rather than only
+    // giving a width or precision
+    let mut used: Vec<&String> = Vec::new();
+    let mut formatted = HashSet::new();
+    let mut counts = Vec::new();
+    for placeholder in &placeholders {
+        let (key, &(_, field)) = lookup(&value[placeholder.start..placeholder.end])?;
+        rewritten.push_str(&value[position..placeholder.start]);
+        rewritten.push_str("__display_");
+        rewritten.push_str(key);
+        position = placeholder.end;
+
+        if !used.contains(&key) {
+            used.push(key);
+        }
+        formatted.insert(key);
+        let ty = peel_references(&field.ty);
+        let bound = &placeholder.bound;
+        let known = bounds.iter().any(|(known_ty, known_bound)| {
+            quote!(#known_ty: #known_bound).to_string() == quote!(#ty: #bound).to_string()
+        });
+        if !known && mentions_any(ty, type_params) {
+            bounds.push((ty.clone(), bound.clone()));
+        }
+
+        // Widths and precisions must be `usize` values rather than the references the pattern
+        // binds, so they are passed dereferenced as positional arguments
+        for &(start, end) in &placeholder.counts {
+            let (key, _) = lookup(&value[start..end])?;
+            rewritten.push_str(&value[position..start]);
+            rewritten.push_str(&counts.len().to_string());
+            position = end;
+
+            if !used.contains(&key) {
+                used.push(key);
+            }
+            counts.push(format_ident!("__display_{}", key, span = Span::call_site()));
+        }
+    }
+    rewritten.push_str(&value[position..]);
+
+    let mut used: Vec<(usize, &String)> = used.into_iter().map(|key| (by_key[key].0, key)).collect();
+    used.sort();
+    let bindings: Vec<syn::Ident> = used.iter()
+        .map(|(_, key)| format_ident!("__display_{}", key, span = Span::call_site()))
+        .collect();
+    let pattern = match fields {
+        syn::Fields::Named(_) => {
+            let members = used.iter().map(|(_, key)| format_ident!("{}", key));
+            quote! { { #( #members: #bindings, )* .. } }
+        }
+        syn::Fields::Unnamed(_) => {
+            let members = used.iter().map(|&(i, _)| syn::Index::from(i));
+            quote! { { #( #members: #bindings, )* .. } }
+        }
+        syn::Fields::Unit => quote! {},
+    };
+
+    let arguments = used.iter().zip(&bindings)
+        .filter(|((_, key), _)| formatted.contains(key))
+        .map(|(_, binding)| binding);
+    let rewritten = syn::LitStr::new(&rewritten, format.span());
+    Ok((pattern, quote! { write!(f, #rewritten, #( *#counts, )* #( #arguments = #arguments ),*) }))
+}
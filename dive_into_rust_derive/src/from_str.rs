@@ -0,0 +1,238 @@
+//! Code generation for `#[derive(FromStr)]`.
+
+use proc_macro2::TokenStream;
+use syn::spanned::Spanned;
+
+/// Settings from `#[from_str(...)]` on the type.
+#[derive(Default)]
+struct Options {
+    /// `sep = ","`: the delimiter between struct fields.
+    sep: Option<syn::LitStr>,
+    /// `case_insensitive`: variant names match regardless of ASCII case.
+    case_insensitive: Option<syn::Path>,
+}
+
+fn from_str_attribute(attrs: &[syn::Attribute]) -> syn::Result<Options> {
+    let mut options = Options::default();
+
+    for attr in attrs {
+        if attr.path().is_ident("from_str") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("sep") {
+                    let sep: syn::LitStr = meta.value()?.parse()?;
+                    if sep.value().is_empty() {
+                        return Err(syn::Error::new(sep.span(), "The separator can't be empty"));
+                    }
+                    options.sep = Some(sep);
+                    Ok(())
+                } else if meta.path.is_ident("case_insensitive") {
+                    options.case_insensitive = Some(meta.path.clone());
+                    Ok(())
+                } else {
+                    Err(meta.error("Unknown from_str option, expected `sep = \"...\"` or `case_insensitive`"))
+                }
+            })?;
+        }
+    }
+
+    Ok(options)
+}
+
+pub fn derive(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let options = from_str_attribute(&ast.attrs)?;
+
+    match &ast.data {
+        syn::Data::Enum(data) => {
+            if let Some(sep) = &options.sep {
+                return Err(syn::Error::new(sep.span(), "`sep` only applies to structs"));
+            }
+            derive_enum(ast, data, &options)
+        }
+        syn::Data::Struct(data) => {
+            if let Some(path) = &options.case_insensitive {
+                return Err(syn::Error::new_spanned(path, "`case_insensitive` only applies to enums"));
+            }
+            derive_struct(ast, data, &options)
+        }
+        syn::Data::Union(_) => Err(syn::Error::new(ast.ident.span(), "FromStr can't be derived for unions")),
+    }
+}
+
+/// Unit enums parse the name of a variant. The error is `Parse<Enum>Error`, holding the input.
+fn derive_enum(ast: &syn::DeriveInput, data: &syn::DataEnum, options: &Options) -> syn::Result<TokenStream> {
+    for variant in &data.variants {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                &variant.fields,
+                format!("FromStr needs unit variants, but `{}` has fields", variant.ident),
+            ));
+        }
+    }
+
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    let error = format_ident!("Parse{}Error", name);
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let variants: Vec<&syn::Ident> = data.variants.iter().map(|variant| &variant.ident).collect();
+    let names: Vec<String> = variants.iter().map(|variant| variant.to_string()).collect();
+    let expected = names.iter().map(|name| format!("`{}`", name)).collect::<Vec<_>>().join(", ");
+
+    let matching = if options.case_insensitive.is_some() {
+        quote! {
+            #( if s.eq_ignore_ascii_case(#names) { return Ok(#name::#variants); } )*
+        }
+    } else {
+        quote! {
+            match s {
+                #( #names => return Ok(#name::#variants), )*
+                _ => (),
+            }
+        }
+    };
+    let message = format!("Unknown variant `{{}}` for {}, expected one of {}", name, expected);
+
+    Ok(quote! {
+        /// The error returned when the input is not the name of a variant.
+        #[derive(Debug, Clone, PartialEq)]
+        #vis struct #error {
+            pub input: String,
+        }
+
+        impl ::std::fmt::Display for #error {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                write!(f, #message, self.input)
+            }
+        }
+
+        impl ::std::error::Error for #error {}
+
+        impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+            type Err = #error;
+
+            fn from_str(s: &str) -> Result<Self, #error> {
+                #matching
+                Err(#error { input: s.to_owned() })
+            }
+        }
+    })
+}
+
+/// Structs parse each field with its own `FromStr`, from a layout split on `sep`. A struct with a
+/// single field may leave out `sep` and parse the whole input. The error is `Parse<Struct>Error`,
+/// which names the failing field.
+fn derive_struct(ast: &syn::DeriveInput, data: &syn::DataStruct, options: &Options) -> syn::Result<TokenStream> {
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    let error = format_ident!("Parse{}Error", name);
+    let count = data.fields.len();
+
+    if count == 0 {
+        return Err(syn::Error::new(ast.ident.span(), "FromStr needs a struct with fields"));
+    }
+    if count > 1 && options.sep.is_none() {
+        return Err(syn::Error::new(
+            ast.ident.span(),
+            "FromStr needs a separator for structs with several fields, e.g. #[from_str(sep = \",\")]",
+        ));
+    }
+
+    // Field types get the bounds the generated parsing relies on
+    let mut generics = ast.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for field in &data.fields {
+        let ty = &field.ty;
+        where_clause.predicates.push(syn::parse_quote! { #ty: ::std::str::FromStr });
+        where_clause.predicates.push(syn::parse_quote! {
+            <#ty as ::std::str::FromStr>::Err: ::std::error::Error + Send + Sync + 'static
+        });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let parts = match &options.sep {
+        // One more part than fields, so leftover input isn't silently dropped
+        Some(sep) => quote! { let mut __from_str_parts = s.splitn(#count + 1, #sep); },
+        None => quote! { let mut __from_str_parts = ::std::iter::once(s); },
+    };
+
+    let mut bindings = Vec::new();
+    let mut parse_quote = quote! {};
+    for (i, field) in data.fields.iter().enumerate() {
+        let field_name = match &field.ident {
+            Some(ident) => ident.to_string(),
+            None => i.to_string(),
+        };
+        let binding = format_ident!("__from_str_field_{}", field_name);
+        let ty = &field.ty;
+        parse_quote.extend(quote_spanned! {field.span()=>
+            let #binding = {
+                let __from_str_input = __from_str_parts.next()
+                    .ok_or(#error::Missing { field: #field_name })?;
+                <#ty as ::std::str::FromStr>::from_str(__from_str_input).map_err(|err| #error::Invalid {
+                    field: #field_name,
+                    input: __from_str_input.to_owned(),
+                    source: Box::new(err),
+                })?
+            };
+        });
+        bindings.push(binding);
+    }
+
+    let construct = match &data.fields {
+        syn::Fields::Named(fields) => {
+            let members = fields.named.iter().map(|field| &field.ident);
+            quote! { #name { #( #members: #bindings ),* } }
+        }
+        _ => quote! { #name ( #( #bindings ),* ) },
+    };
+
+    Ok(quote! {
+        /// The error returned when a field of the input can't be parsed.
+        #[derive(Debug)]
+        #vis enum #error {
+            /// The input ended before this field.
+            Missing { field: &'static str },
+            /// The field's own `FromStr` rejected its part of the input.
+            Invalid {
+                field: &'static str,
+                input: String,
+                source: Box<dyn ::std::error::Error + Send + Sync>,
+            },
+            /// Input left over after the last field.
+            Trailing { input: String },
+        }
+
+        impl ::std::fmt::Display for #error {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                match self {
+                    #error::Missing { field } => write!(f, "Missing value for field `{}`", field),
+                    #error::Invalid { field, input, source } => {
+                        write!(f, "Invalid value `{}` for field `{}`: {}", input, field, source)
+                    }
+                    #error::Trailing { input } => write!(f, "Unexpected trailing input `{}`", input),
+                }
+            }
+        }
+
+        impl ::std::error::Error for #error {
+            fn source(&self) -> Option<&(dyn ::std::error::Error + 'static)> {
+                match self {
+                    #error::Invalid { source, .. } => Some(&**source),
+                    _ => None,
+                }
+            }
+        }
+
+        impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+            type Err = #error;
+
+            fn from_str(s: &str) -> Result<Self, #error> {
+                #parts
+                #parse_quote
+                if let Some(rest) = __from_str_parts.next() {
+                    return Err(#error::Trailing { input: rest.to_owned() });
+                }
+                Ok(#construct)
+            }
+        }
+    })
+}
@@ -0,0 +1,176 @@
+//! Code generation for the `#[memoize]` attribute.
+
+use proc_macro2::TokenStream;
+use syn::parse::Parser;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+/// Settings from `#[memoize(...)]`.
+#[derive(Default)]
+struct Options {
+    /// `global`: one cache shared by every thread instead of one per thread.
+    global: bool,
+    /// `capacity = N`: evict the oldest entry once the cache holds `N` results.
+    capacity: Option<usize>,
+}
+
+fn parse_options(args: TokenStream) -> syn::Result<Options> {
+    let mut options = Options::default();
+
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("global") {
+            options.global = true;
+            Ok(())
+        } else if meta.path.is_ident("capacity") {
+            let lit: syn::LitInt = meta.value()?.parse()?;
+            let capacity = lit.base10_parse()?;
+            if capacity == 0 {
+                return Err(syn::Error::new(lit.span(), "capacity must be at least 1"));
+            }
+            options.capacity = Some(capacity);
+            Ok(())
+        } else {
+            Err(meta.error("Unknown memoize option, expected `global` or `capacity = N`"))
+        }
+    });
+    parser.parse2(args)?;
+
+    Ok(options)
+}
+
+/// Finds borrows and `impl Trait` in an argument type, which can't be part of the static cache's
+/// key type. `'static` references are fine.
+#[derive(Default)]
+struct Borrowed(bool);
+
+impl<'ast> Visit<'ast> for Borrowed {
+    fn visit_type_reference(&mut self, reference: &'ast syn::TypeReference) {
+        self.0 |= reference.lifetime.is_none();
+        syn::visit::visit_type_reference(self, reference);
+    }
+
+    fn visit_lifetime(&mut self, lifetime: &'ast syn::Lifetime) {
+        self.0 |= lifetime.ident != "static";
+    }
+
+    fn visit_type_impl_trait(&mut self, _: &'ast syn::TypeImplTrait) {
+        self.0 = true;
+    }
+}
+
+/// Keeps the function's results in a cache keyed on a tuple of its arguments, and adds
+/// `<name>_clear_cache()`. The cache is a static, so only free, non-generic functions with owned
+/// arguments qualify.
+pub fn expand(args: TokenStream, function: &syn::ItemFn) -> syn::Result<TokenStream> {
+    let options = parse_options(args)?;
+    let sig = &function.sig;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new(asyncness.span, "memoize doesn't support async functions"));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new(sig.generics.span(), "memoize doesn't support generic functions"));
+    }
+    let output = match &sig.output {
+        syn::ReturnType::Type(_, ty) => ty,
+        syn::ReturnType::Default => return Err(syn::Error::new(sig.ident.span(), "memoize needs a return value to cache")),
+    };
+
+    let mut arguments = Vec::new();
+    let mut types = Vec::new();
+    for input in &sig.inputs {
+        match input {
+            syn::FnArg::Typed(typed) => match &*typed.pat {
+                syn::Pat::Ident(pat) => {
+                    let mut borrowed = Borrowed::default();
+                    borrowed.visit_type(&typed.ty);
+                    if borrowed.0 {
+                        return Err(syn::Error::new_spanned(
+                            &typed.ty,
+                            "memoize arguments must be owned 'static types (used as cache keys)",
+                        ));
+                    }
+                    arguments.push(&pat.ident);
+                    types.push(&*typed.ty);
+                }
+                pat => return Err(syn::Error::new(pat.span(), "memoize needs arguments bound to a name")),
+            },
+            syn::FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new(receiver.span(), "memoize only supports free functions"));
+            }
+        }
+    }
+
+    let name = &sig.ident;
+    let vis = &function.vis;
+    let attrs = &function.attrs;
+    let block = &function.block;
+    let cache = format_ident!("__memoize_{}", name);
+    let clear = format_ident!("{}_clear_cache", name);
+    let clear_doc = format!("Forgets every result cached by `{}`.", name);
+    let key = quote! { ( #( #types, )* ) };
+    let storage = quote! {
+        (::std::collections::HashMap<#key, #output>, ::std::collections::VecDeque<#key>)
+    };
+
+    let access = if options.global {
+        quote! {
+            static CACHE: ::std::sync::Mutex<Option<#storage>> = ::std::sync::Mutex::new(None);
+            let mut cache = CACHE.lock().unwrap_or_else(::std::sync::PoisonError::into_inner);
+            f(cache.get_or_insert_with(::std::default::Default::default))
+        }
+    } else {
+        quote! {
+            ::std::thread_local! {
+                static CACHE: ::std::cell::RefCell<#storage> = ::std::cell::RefCell::new(::std::default::Default::default());
+            }
+            CACHE.with(|cache| f(&mut cache.borrow_mut()))
+        }
+    };
+
+    // Insertion order is only tracked when there is a capacity to enforce
+    let evict = match options.capacity {
+        Some(capacity) => quote! {
+            if cache.0.len() >= #capacity {
+                if let Some(oldest) = cache.1.pop_front() {
+                    cache.0.remove(&oldest);
+                }
+            }
+            cache.1.push_back(::std::clone::Clone::clone(&__memoize_key));
+        },
+        None => quote! {},
+    };
+
+    // The lock is released while the body runs, so recursive calls can use the cache too
+    Ok(quote! {
+        fn #cache<__R>(f: impl ::std::ops::FnOnce(&mut #storage) -> __R) -> __R {
+            #access
+        }
+
+        #( #attrs )*
+        #vis #sig {
+            let __memoize_key = ( #( ::std::clone::Clone::clone(&#arguments), )* );
+            if let Some(value) = #cache(|cache| cache.0.get(&__memoize_key).cloned()) {
+                return value;
+            }
+
+            #[allow(clippy::redundant_closure_call)]
+            let __memoize_value = (move || -> #output #block)();
+            #cache(|cache| {
+                if !cache.0.contains_key(&__memoize_key) {
+                    #evict
+                    cache.0.insert(__memoize_key, ::std::clone::Clone::clone(&__memoize_value));
+                }
+            });
+            __memoize_value
+        }
+
+        #[doc = #clear_doc]
+        #[allow(dead_code)]
+        #vis fn #clear() {
+            #cache(|cache| {
+                cache.0.clear();
+                cache.1.clear();
+            })
+        }
+    })
+}
@@ -0,0 +1,165 @@
+//! Code generation for `#[derive(OrderBy)]`.
+
+use proc_macro2::TokenStream;
+use std::collections::HashSet;
+use syn::spanned::Spanned;
+
+use crate::types::{mentions_any, option_type, peel_references, type_name};
+
+/// Settings from `#[order(...)]` on the struct or on a field.
+#[derive(Default)]
+struct Options {
+    /// `by = "a, b"`: the key fields, most significant first.
+    by: Option<(Vec<String>, syn::LitStr)>,
+    reverse: bool,
+    ignore: bool,
+}
+
+fn order_attribute(attrs: &[syn::Attribute], on_field: bool) -> syn::Result<Options> {
+    let mut options = Options::default();
+
+    for attr in attrs {
+        if attr.path().is_ident("order") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("by") && !on_field {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    let keys = lit.value().split(',').map(|key| key.trim().to_owned()).collect();
+                    options.by = Some((keys, lit));
+                } else if meta.path.is_ident("reverse") {
+                    options.reverse = true;
+                } else if meta.path.is_ident("ignore") && on_field {
+                    options.ignore = true;
+                } else if on_field {
+                    return Err(meta.error("Unknown order option, expected `reverse` or `ignore`"));
+                } else {
+                    return Err(meta.error("Unknown order option, expected `by = \"...\"` or `reverse`"));
+                }
+                Ok(())
+            })?;
+        }
+    }
+
+    Ok(options)
+}
+
+/// A field that takes part in the comparison.
+struct Key<'a> {
+    member: syn::Member,
+    field: &'a syn::Field,
+    reverse: bool,
+}
+
+/// `f32` or `f64`, also behind references.
+fn float_type(ty: &syn::Type) -> Option<syn::Ident> {
+    match peel_references(ty) {
+        syn::Type::Path(_) => type_name(ty).filter(|name| name == "f32" || name == "f64").map(|name| format_ident!("{}", name)),
+        _ => None,
+    }
+}
+
+/// `Ord`, `PartialOrd`, `PartialEq` and `Eq` comparing the key fields in turn. Float fields, also
+/// behind references or in an `Option`, compare by `total_cmp`, and other types that contain
+/// floats are rejected. The derive only sees tokens, so a type alias for a float isn't recognised
+/// and fails to compile for lack of `Ord`.
+pub fn derive(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &ast.data {
+        syn::Data::Struct(data) => &data.fields,
+        _ => return Err(syn::Error::new(ast.ident.span(), "OrderBy can only be derived for structs")),
+    };
+    let options = order_attribute(&ast.attrs, false)?;
+
+    let mut all = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(syn::Index::from(i)),
+        };
+        let name = match &field.ident {
+            Some(ident) => ident.to_string(),
+            None => i.to_string(),
+        };
+        let field_options = order_attribute(&field.attrs, true)?;
+        all.push((name, Key { member, field, reverse: field_options.reverse }, field_options.ignore));
+    }
+
+    // Without `by`, every field that isn't ignored is a key, in declaration order
+    let keys: Vec<&Key> = match &options.by {
+        Some((names, lit)) => names.iter()
+            .map(|name| {
+                let (_, key, ignore) = all.iter().find(|(field_name, ..)| field_name == name).ok_or_else(|| {
+                    syn::Error::new(lit.span(), format!("Could not locate key field `{}`", name))
+                })?;
+                if *ignore {
+                    return Err(syn::Error::new(lit.span(), format!("Key field `{}` is also ignored", name)));
+                }
+                Ok(key)
+            })
+            .collect::<syn::Result<_>>()?,
+        None => all.iter().filter(|(_, _, ignore)| !ignore).map(|(_, key, _)| key).collect(),
+    };
+
+    let type_params: HashSet<String> = ast.generics.type_params().map(|param| param.ident.to_string()).collect();
+    let floats: HashSet<String> = ["f32", "f64"].iter().map(|name| name.to_string()).collect();
+    let mut generics = ast.generics.clone();
+    let mut comparisons = quote! {};
+    for key in &keys {
+        let member = &key.member;
+        let ty = &key.field.ty;
+        let (left, right) = if key.reverse {
+            (quote! { other.#member }, quote! { self.#member })
+        } else {
+            (quote! { self.#member }, quote! { other.#member })
+        };
+
+        // Floats have no `Ord`, so they compare by `total_cmp`, which orders NaN too
+        let compare = if let Some(float) = float_type(ty) {
+            quote_spanned! {ty.span()=> #float::total_cmp(&#left, &#right) }
+        } else if let Some(float) = option_type(ty).and_then(float_type) {
+            // `None` sorts first, like `Option`'s own `Ord`
+            quote_spanned! {ty.span()=>
+                match (&#left, &#right) {
+                    (::std::option::Option::Some(left), ::std::option::Option::Some(right)) => #float::total_cmp(left, right),
+                    (left, right) => ::std::cmp::Ord::cmp(&left.is_some(), &right.is_some()),
+                }
+            }
+        } else if mentions_any(ty, &floats) {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "OrderBy can only order floats in fields of type `f32`/`f64`, references to them or `Option`s of them; \
+                 use #[order(ignore)] to leave this field out",
+            ));
+        } else {
+            if mentions_any(ty, &type_params) {
+                let ty = peel_references(ty);
+                generics.make_where_clause().predicates.push(syn::parse_quote! { #ty: ::std::cmp::Ord });
+            }
+            quote_spanned! {ty.span()=> ::std::cmp::Ord::cmp(&#left, &#right) }
+        };
+        comparisons.extend(quote! { .then_with(|| #compare) });
+    }
+    let reverse = if options.reverse { quote! { .reverse() } } else { quote! {} };
+
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::std::cmp::Ord for #name #ty_generics #where_clause {
+            fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+                ::std::cmp::Ordering::Equal #comparisons #reverse
+            }
+        }
+
+        impl #impl_generics ::std::cmp::PartialOrd for #name #ty_generics #where_clause {
+            fn partial_cmp(&self, other: &Self) -> Option<::std::cmp::Ordering> {
+                Some(::std::cmp::Ord::cmp(self, other))
+            }
+        }
+
+        impl #impl_generics ::std::cmp::PartialEq for #name #ty_generics #where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                ::std::cmp::Ord::cmp(self, other) == ::std::cmp::Ordering::Equal
+            }
+        }
+
+        impl #impl_generics ::std::cmp::Eq for #name #ty_generics #where_clause {}
+    })
+}
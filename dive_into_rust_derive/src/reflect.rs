@@ -0,0 +1,105 @@
+//! Code generation for `#[derive(EnumReflect)]`.
+
+use proc_macro2::TokenStream;
+
+/// The integer types that get a `TryFrom` impl.
+const INTEGER_TYPES: &[&str] = &["i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize"];
+
+/// The integer type from `#[repr(u8)]` and the like, which the discriminants are compared in.
+/// Without one the compiler uses `isize`.
+fn repr_type(attrs: &[syn::Attribute]) -> syn::Result<syn::Ident> {
+    let mut repr = format_ident!("isize");
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        // Other hints such as `C` or `align(4)` don't change the discriminant type
+        attr.parse_nested_meta(|meta| {
+            if let Some(ident) = meta.path.get_ident().filter(|ident| INTEGER_TYPES.iter().any(|ty| ident == ty)) {
+                repr = ident.clone();
+            } else if meta.input.peek(syn::token::Paren) {
+                meta.input.parse::<proc_macro2::Group>()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(repr)
+}
+
+pub fn derive(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let data = match &ast.data {
+        syn::Data::Enum(data) => data,
+        _ => return Err(syn::Error::new(ast.ident.span(), "EnumReflect can only be derived for enums")),
+    };
+
+    // Only C-like enums have discriminants to convert from
+    for variant in &data.variants {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                &variant.fields,
+                format!("EnumReflect needs unit variants, but `{}` has fields", variant.ident),
+            ));
+        }
+    }
+
+    let name = &ast.ident;
+    let repr = repr_type(&ast.attrs)?;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let variants: Vec<&syn::Ident> = data.variants.iter().map(|variant| &variant.ident).collect();
+    let names: Vec<String> = variants.iter().map(|variant| variant.to_string()).collect();
+    let count = variants.len();
+
+    let name_body = if variants.is_empty() {
+        quote! { match *self {} }
+    } else {
+        quote! { match *self { #( #name::#variants => #names, )* } }
+    };
+
+    let try_from_quote = INTEGER_TYPES.iter()
+        .map(|ty| {
+            let ty = format_ident!("{}", ty);
+            quote! {
+                impl #impl_generics ::std::convert::TryFrom<#ty> for #name #ty_generics #where_clause {
+                    /// The value that isn't the discriminant of any variant.
+                    type Error = #ty;
+
+                    fn try_from(value: #ty) -> Result<Self, #ty> {
+                        <#repr as ::std::convert::TryFrom<#ty>>::try_from(value)
+                            .ok()
+                            .and_then(Self::from_discriminant)
+                            .ok_or(value)
+                    }
+                }
+            }
+        })
+        .collect::<TokenStream>();
+
+    Ok(quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The names of the variants, in declaration order.
+            pub const VARIANTS: &'static [&'static str] = &[#(#names),*];
+
+            /// The number of variants.
+            pub const COUNT: usize = #count;
+
+            /// The name of this variant, as written in the enum.
+            pub fn name(&self) -> &'static str {
+                #name_body
+            }
+
+            /// Every variant, in declaration order.
+            pub fn iter() -> impl DoubleEndedIterator<Item = Self> + ExactSizeIterator {
+                ::std::iter::IntoIterator::into_iter([#( #name::#variants ),*])
+            }
+
+            /// The variant whose discriminant, explicit or implied, is `discriminant`. It has the
+            /// enum's `#[repr]` integer type, `isize` by default.
+            pub fn from_discriminant(discriminant: #repr) -> Option<Self> {
+                match discriminant {
+                    #( d if d == #name::#variants as #repr => Some(#name::#variants), )*
+                    _ => None,
+                }
+            }
+        }
+
+        #try_from_quote
+    })
+}
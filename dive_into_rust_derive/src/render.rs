@@ -0,0 +1,681 @@
+//! Code generation for `#[derive(Render)]`.
+
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::Token;
+
+use dive_into_rust_template::syntax::{self as template, Filter, Node, Path, Source, BUILTIN_FILTERS};
+use dive_into_rust_template::Escape;
+use crate::diagnostics::suggest;
+use crate::types::{element_type, is_string_like, mentions_any, option_type, peel_references, type_name};
+
+/// Capacity reserved for a value whose rendered length can't be worked out from its type.
+const DEFAULT_LENGTH_ESTIMATE: usize = 8;
+
+pub fn derive(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let options = Options {
+        escape: escape_attribute(&ast.attrs)?,
+        filters: filter_attribute(&ast.attrs)?,
+        type_params: ast.generics.type_params().map(|param| param.ident.to_string()).collect(),
+    };
+    let mut bounds = Vec::new();
+    let name = &ast.ident;
+
+    let (length_body, write_body) = match &ast.data {
+        syn::Data::Struct(data) => {
+            let (source, span) = template_source(&ast.attrs, &ast.ident)?;
+            let fields = data.fields.iter().enumerate()
+                .map(|(i, field)| {
+                    let member = match &field.ident {
+                        Some(ident) => syn::Member::Named(ident.clone()),
+                        None => syn::Member::Unnamed(syn::Index::from(i)),
+                    };
+                    let key = match &member {
+                        syn::Member::Named(ident) => ident.to_string(),
+                        syn::Member::Unnamed(index) => index.index.to_string(),
+                    };
+                    (key, Binding { tokens: quote! { self.#member }, field })
+                })
+                .collect();
+            generate_body(fields, source, span, &options, &mut bounds)?
+        }
+        syn::Data::Enum(data) => {
+            let mut arms = quote! {};
+            let mut write_arms = quote! {};
+            for variant in &data.variants {
+                // Each variant has its own template, rendered against its own fields
+                let (source, span) = template_source(&variant.attrs, &variant.ident)?;
+                let mut fields = HashMap::new();
+                let mut patterns = Vec::new();
+                for (i, field) in variant.fields.iter().enumerate() {
+                    let key = match &field.ident {
+                        Some(ident) => ident.to_string(),
+                        None => i.to_string(),
+                    };
+                    let binding = format_ident!("__render_field_{}", key);
+                    patterns.push(match &field.ident {
+                        Some(ident) => quote! { #ident: #binding },
+                        None => quote! { #binding },
+                    });
+                    fields.insert(key, Binding { tokens: quote! { #binding }, field });
+                }
+
+                let pattern = match &variant.fields {
+                    syn::Fields::Named(_) => quote! { { #(#patterns),* } },
+                    syn::Fields::Unnamed(_) => quote! { ( #(#patterns),* ) },
+                    syn::Fields::Unit => quote! {},
+                };
+                let variant_ident = &variant.ident;
+                let (body, write_body) = generate_body(fields, source, span, &options, &mut bounds)?;
+                arms.extend(quote! {
+                    #name::#variant_ident #pattern => { #body }
+                });
+                write_arms.extend(quote! {
+                    #name::#variant_ident #pattern => { #write_body }
+                });
+            }
+
+            if data.variants.is_empty() {
+                (quote! { match *self {} }, quote! { match *self {} })
+            } else {
+                (quote! { match self { #arms } }, quote! { match self { #write_arms } })
+            }
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new(ast.ident.span(), "Render can't be derived for unions"));
+        }
+    };
+
+    // Generic values that get interpolated need to be `Display`
+    let mut generics = ast.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for ty in bounds {
+        where_clause.predicates.push(syn::parse_quote! { #ty: ::std::fmt::Display });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        // `(&items).into_iter()` is deliberate, see `Node::Each`
+        #[allow(clippy::into_iter_on_ref)]
+        impl #impl_generics Render for #name #ty_generics #where_clause {
+            fn render_to<W: ::std::fmt::Write + ?Sized>(&self, writer: &mut W) -> ::std::fmt::Result {
+                #write_body
+            }
+
+            fn render_length(&self) -> usize {
+                #length_body
+            }
+        }
+
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                Render::render_to(self, f)
+            }
+        }
+    })
+}
+
+/// Settings shared by every template of the derived type.
+struct Options {
+    escape: Escape,
+    filters: HashMap<String, syn::Path>,
+    /// Names of the type's generic type parameters.
+    type_params: HashSet<String>,
+}
+
+/// Generates the statements that estimate the rendered length of one template and those that
+/// write it to `writer`. Generic types that need a `Display` bound are added to `bounds`.
+fn generate_body(fields: HashMap<String, Binding>, source: Source, span: Span, options: &Options,
+                 bounds: &mut Vec<syn::Type>) -> syn::Result<(TokenStream, TokenStream)> {
+    // Partials of inline templates are relative to the crate, like `TemplateName`
+    let mut loader = template::Loader::new(source, resolve_path(""));
+    let loaded = loader.load();
+    let mut generator = Generator {
+        fields,
+        options,
+        sources: loader.sources,
+        current: 0,
+        span,
+        scopes: Vec::new(),
+        bounds,
+    };
+
+    let nodes = loaded.map_err(|(source, err)| generator.error_in(source, err))?;
+    let (length_quote, concat_quote) = generator.generate_nodes(&nodes)?;
+
+    let escape_quote = generate_escape(options.escape);
+
+    // Referencing the files makes cargo rebuild when a template changes
+    let dependency_quote = generator.sources.iter()
+        .filter_map(|source| source.path.as_ref())
+        .map(|path| {
+            let path = path.to_string_lossy();
+            quote! { const _: &str = include_str!(#path); }
+        })
+        .collect::<TokenStream>();
+
+    let length_body = quote! {
+        let mut total_length = 0;
+
+        #length_quote
+
+        total_length
+    };
+
+    let write_body = quote! {
+        #dependency_quote
+        #escape_quote
+
+        #concat_quote
+
+        Ok(())
+    };
+
+    Ok((length_body, write_body))
+}
+
+/// Reads `#[TemplateName = "hello.tpl"]`, relative to the crate's `Cargo.toml`, or takes the
+/// template inline from `#[Template = "Hello {{name}}"]`. Template errors are reported on the
+/// returned span; a missing attribute is reported on `ident`, the struct or variant.
+fn template_source(attrs: &[syn::Attribute], ident: &syn::Ident) -> syn::Result<(Source, Span)> {
+    let file = string_attribute(attrs, "TemplateName")?;
+    let inline = string_attribute(attrs, "Template")?;
+
+    match (file, inline) {
+        (Some(_), Some(inline)) => Err(syn::Error::new(
+            inline.span(),
+            "Template and TemplateName can't be used together",
+        )),
+        (Some(file), None) => {
+            let location = file.value();
+            let path = resolve_path(&location);
+            let contents = fs::read_to_string(&path).map_err(|err| {
+                syn::Error::new(file.span(), format!("Couldn't read the file {}: {}", path.display(), err))
+            })?;
+            Ok((Source { contents, location, path: Some(path) }, file.span()))
+        }
+        (None, Some(inline)) => {
+            let source = Source {
+                contents: inline.value(),
+                location: "inline template".to_owned(),
+                path: None,
+            };
+            Ok((source, inline.span()))
+        }
+        (None, None) => Err(syn::Error::new(
+            ident.span(),
+            "Could not find TemplateName attribute, e.g. #[TemplateName = \"hello.tpl\"] \
+             or #[Template = \"Hello {{name}}\"]",
+        )),
+    }
+}
+
+/// Resolves a template path against the directory of the crate being compiled.
+fn resolve_path(location: &str) -> PathBuf {
+    match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(manifest_dir) => ::std::path::Path::new(&manifest_dir).join(location),
+        None => PathBuf::from(location),
+    }
+}
+
+/// Finds `#[name = "..."]` and returns its string literal.
+fn string_attribute(attrs: &[syn::Attribute], name: &str) -> syn::Result<Option<syn::LitStr>> {
+    for attr in attrs {
+        if attr.path().is_ident(name) {
+            let name_value = attr.meta.require_name_value()?;
+            return match &name_value.value {
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) => Ok(Some(lit.clone())),
+                value => Err(syn::Error::new_spanned(
+                    value,
+                    format!("{} expects a string literal, e.g. #[{} = \"...\"]", name, name),
+                )),
+            };
+        }
+    }
+
+    Ok(None)
+}
+
+/// Collects `#[TemplateFilter(slug = path::to::slugify)]` registrations. A filter function takes
+/// the rendered `&str`, then any arguments given in the template, and returns a `String`.
+fn filter_attribute(attrs: &[syn::Attribute]) -> syn::Result<HashMap<String, syn::Path>> {
+    let mut filters = HashMap::new();
+
+    for attr in attrs {
+        if attr.path().is_ident("TemplateFilter") {
+            attr.parse_nested_meta(|meta| {
+                let name = meta.path.require_ident()?.to_string();
+                if BUILTIN_FILTERS.iter().any(|&(builtin, _)| builtin == name) {
+                    return Err(meta.error(format!("`{}` is a built-in filter", name)));
+                }
+                filters.insert(name, meta.value()?.parse()?);
+                Ok(())
+            })?;
+        }
+    }
+
+    Ok(filters)
+}
+
+/// Reads `#[TemplateEscape = "html"]`, defaulting to no escaping.
+fn escape_attribute(attrs: &[syn::Attribute]) -> syn::Result<Escape> {
+    let lit = match string_attribute(attrs, "TemplateEscape")? {
+        Some(lit) => lit,
+        None => return Ok(Escape::None),
+    };
+
+    Escape::from_name(&lit.value())
+        .ok_or_else(|| syn::Error::new(lit.span(), "TemplateEscape expects \"html\", \"xml\" or \"none\""))
+}
+
+/// A `fmt::Write` adapter that escapes everything written through it before passing it on.
+fn generate_escape(escape: Escape) -> TokenStream {
+    if escape == Escape::None {
+        return quote! {};
+    }
+
+    let (chars, entities): (Vec<char>, Vec<&str>) = escape.entities().iter().cloned().unzip();
+    quote! {
+        struct __RenderEscape<'a, W: ::std::fmt::Write + ?Sized>(&'a mut W);
+
+        impl<'a, W: ::std::fmt::Write + ?Sized> ::std::fmt::Write for __RenderEscape<'a, W> {
+            fn write_str(&mut self, s: &str) -> ::std::fmt::Result {
+                // Pass the text between special characters through in one piece
+                let mut start = 0;
+                for (i, c) in s.char_indices() {
+                    let entity = match c {
+                        #( #chars => #entities, )*
+                        _ => continue,
+                    };
+                    self.0.write_str(&s[start..i])?;
+                    self.0.write_str(entity)?;
+                    start = i + c.len_utf8();
+                }
+                self.0.write_str(&s[start..])
+            }
+        }
+    }
+}
+
+/// The bindings introduced by an enclosing `{{#each}}` or `{{#with}}` block.
+struct Scope {
+    this: syn::Ident,
+    ty: Option<syn::Type>,
+    index: Option<syn::Ident>,
+}
+
+/// A value referenced from the template, together with what is known about it.
+struct Value {
+    tokens: TokenStream,
+    ty: Option<syn::Type>,
+    /// The field declaration when the path starts at a struct field, so type errors point there.
+    span: Span,
+}
+
+/// How a struct or variant field is reached from the generated code: `self.name` for structs,
+/// a pattern binding for enum variants.
+struct Binding<'a> {
+    tokens: TokenStream,
+    field: &'a syn::Field,
+}
+
+struct Generator<'a> {
+    /// Fields by name, or by position (`0`, `1`, ...) for tuple structs and variants.
+    fields: HashMap<String, Binding<'a>>,
+    options: &'a Options,
+    /// The template followed by every partial it includes.
+    sources: Vec<Source>,
+    /// The source whose nodes are being generated, for error messages.
+    current: usize,
+    span: Span,
+    scopes: Vec<Scope>,
+    bounds: &'a mut Vec<syn::Type>,
+}
+
+impl<'a> Generator<'a> {
+    /// Reports a template mistake on the `TemplateName` attribute, with its line and column.
+    fn error(&self, err: template::Error) -> syn::Error {
+        self.error_in(self.current, err)
+    }
+
+    fn error_in(&self, source: usize, err: template::Error) -> syn::Error {
+        let source = &self.sources[source];
+        let (line, column) = template::line_column(&source.contents, err.offset);
+        syn::Error::new(self.span, format!("{}:{}:{}: {}", source.location, line, column, err.message))
+    }
+
+    fn generate_nodes(&mut self, nodes: &[Node]) -> syn::Result<(TokenStream, TokenStream)> {
+        let mut length_quote = quote! {};
+        let mut concat_quote = quote! {};
+
+        for node in nodes {
+            match node {
+                Node::Partial(partial) => {
+                    // Partials share the scope of the place they are included from
+                    let including = ::std::mem::replace(&mut self.current, partial.source);
+                    let generated = self.generate_nodes(&partial.nodes);
+                    self.current = including;
+
+                    let (partial_length, partial_concat) = generated?;
+                    length_quote.extend(partial_length);
+                    concat_quote.extend(partial_concat);
+                }
+                Node::Text(text) => {
+                    length_quote.extend(quote! {
+                        total_length += #text.len();
+                    });
+
+                    concat_quote.extend(quote! {
+                        ::std::fmt::Write::write_str(&mut *writer, #text)?;
+                    });
+                }
+                Node::Field(path, filters) | Node::Raw(path, filters) => {
+                    let value = self.generate_path(path)?;
+                    let output = if self.options.escape != Escape::None && matches!(node, Node::Field(..)) {
+                        quote! { &mut __RenderEscape(&mut *writer) }
+                    } else {
+                        quote! { &mut *writer }
+                    };
+
+                    let tokens = &value.tokens;
+                    if !filters.is_empty() || !value.ty.as_ref().is_some_and(is_string_like) {
+                        self.require_display(&value);
+                    }
+                    let length = if !filters.is_empty() {
+                        let (length, filtered) = self.generate_filters(&value, filters)?;
+                        concat_quote.extend(quote! {
+                            let __render_value: String = #filtered;
+                            ::std::fmt::Write::write_str(#output, &__render_value)?;
+                        });
+                        length
+                    } else if value.ty.as_ref().is_some_and(is_string_like) {
+                        concat_quote.extend(quote! {
+                            ::std::fmt::Write::write_str(#output, &#tokens)?;
+                        });
+                        generate_length(&value)
+                    } else {
+                        let arguments = quote_spanned! {value.span=> format_args!("{}", #tokens) };
+                        concat_quote.extend(quote! {
+                            ::std::fmt::Write::write_fmt(#output, #arguments)?;
+                        });
+                        generate_length(&value)
+                    };
+
+                    length_quote.extend(quote! {
+                        total_length += #length;
+                    });
+                }
+                Node::Each(path, body) => {
+                    let collection = self.generate_path(path)?;
+                    let depth = self.scopes.len();
+                    let this = format_ident!("__render_this_{}", depth);
+                    let index = format_ident!("__render_index_{}", depth);
+
+                    let ty = collection.ty.as_ref().and_then(element_type).cloned();
+                    self.scopes.push(Scope { this: this.clone(), ty, index: Some(index.clone()) });
+                    let body = self.generate_nodes(body);
+                    self.scopes.pop();
+                    let (body_length, body_concat) = body?;
+
+                    // The method call auto-derefs, so collections that are themselves references,
+                    // like `this` in a nested `#each` or a field bound by a variant pattern, work too
+                    let iter = &collection.tokens;
+                    let iter = quote_spanned! {collection.span=> (&#iter).into_iter() };
+                    length_quote.extend(quote! {
+                        for (#index, #this) in #iter.enumerate() {
+                            #body_length
+                        }
+                    });
+
+                    concat_quote.extend(quote! {
+                        for (#index, #this) in #iter.enumerate() {
+                            #body_concat
+                        }
+                    });
+                }
+                Node::If(path, then, otherwise) => {
+                    let condition = self.generate_path(path)?;
+                    let (then_length, then_concat) = self.generate_nodes(then)?;
+                    let (else_length, else_concat) = self.generate_nodes(otherwise)?;
+
+                    // `&bool` coerces from both `bool` fields and `&bool` loop items
+                    let tokens = &condition.tokens;
+                    let condition = quote_spanned! {condition.span=> &#tokens };
+                    length_quote.extend(quote! {
+                        let __render_condition: &bool = #condition;
+                        if *__render_condition {
+                            #then_length
+                        } else {
+                            #else_length
+                        }
+                    });
+
+                    concat_quote.extend(quote! {
+                        let __render_condition: &bool = #condition;
+                        if *__render_condition {
+                            #then_concat
+                        } else {
+                            #else_concat
+                        }
+                    });
+                }
+                Node::With(path, body, otherwise) => {
+                    let option = self.generate_path(path)?;
+                    let this = format_ident!("__render_this_{}", self.scopes.len());
+                    let (else_length, else_concat) = self.generate_nodes(otherwise)?;
+
+                    let ty = option.ty.as_ref().and_then(option_type).cloned();
+                    self.scopes.push(Scope { this: this.clone(), ty, index: None });
+                    let body = self.generate_nodes(body);
+                    self.scopes.pop();
+                    let (body_length, body_concat) = body?;
+
+                    let tokens = &option.tokens;
+                    let option = quote_spanned! {option.span=> &#tokens };
+                    length_quote.extend(quote! {
+                        if let Some(#this) = #option {
+                            #body_length
+                        } else {
+                            #else_length
+                        }
+                    });
+
+                    concat_quote.extend(quote! {
+                        if let Some(#this) = #option {
+                            #body_concat
+                        } else {
+                            #else_concat
+                        }
+                    });
+                }
+            }
+        }
+
+        Ok((length_quote, concat_quote))
+    }
+
+    /// Records a `Display` bound for a value whose type involves the generic parameters.
+    fn require_display(&mut self, value: &Value) {
+        if let Some(ty) = &value.ty {
+            // `&T` is `Display` whenever `T` is
+            let ty = peel_references(ty);
+            let key = ty.to_token_stream().to_string();
+            let bounded = self.bounds.iter().any(|bound| bound.to_token_stream().to_string() == key);
+            if !bounded && mentions_any(ty, &self.options.type_params) {
+                self.bounds.push(ty.clone());
+            }
+        }
+    }
+
+    /// Renders the value to a `String` and pipes it through each filter in turn. The length is
+    /// the estimate for the unfiltered value, bounded by filters that cap or pad it.
+    fn generate_filters(&self, value: &Value, filters: &[Filter]) -> syn::Result<(TokenStream, TokenStream)> {
+        let mut length = generate_length(value);
+        let tokens = &value.tokens;
+        let mut steps = quote_spanned! {value.span=>
+            let __render_value: String = ::std::string::ToString::to_string(&#tokens);
+        };
+
+        for filter in filters {
+            let arguments = Punctuated::<syn::Expr, Token![,]>::parse_terminated
+                .parse_str(&filter.arguments)
+                .map_err(|err| self.error(template::Error::new(
+                    format!("Invalid arguments for filter `{}`: {}", filter.name, err),
+                    filter.offset,
+                )))?;
+            let arguments: Vec<&syn::Expr> = arguments.iter().collect();
+
+            let step = match (filter.name.as_str(), arguments.as_slice()) {
+                ("upper", []) => quote! { __render_value.to_uppercase() },
+                ("lower", []) => quote! { __render_value.to_lowercase() },
+                ("trim", []) => quote! { __render_value.trim().to_owned() },
+                ("truncate", [count]) => {
+                    length = quote! { ::std::cmp::min(#length, #count) };
+                    quote! { __render_value.chars().take(#count).collect::<String>() }
+                }
+                ("pad_left", [width]) | ("pad_left", [width, _]) => {
+                    let fill = match arguments.get(1) {
+                        Some(fill) => quote! { #fill },
+                        None => quote! { ' ' },
+                    };
+                    length = quote! { ::std::cmp::max(#length, #width) };
+                    quote! {{
+                        let __render_width: usize = #width;
+                        let __render_fill: char = #fill;
+                        let mut __render_padded = String::with_capacity(__render_width);
+                        for _ in __render_value.chars().count()..__render_width {
+                            __render_padded.push(__render_fill);
+                        }
+                        __render_padded + &__render_value
+                    }}
+                }
+                (name, _) if BUILTIN_FILTERS.iter().any(|&(builtin, _)| builtin == name) => {
+                    let usage = BUILTIN_FILTERS.iter().find(|&&(builtin, _)| builtin == name).unwrap().1;
+                    return Err(self.error(template::Error::new(
+                        format!("Wrong arguments for filter `{}`, expected `{}`", name, usage),
+                        filter.offset,
+                    )));
+                }
+                (name, arguments) => match self.options.filters.get(name) {
+                    Some(function) => quote! { #function(&__render_value #(, #arguments)*) },
+                    None => {
+                        let mut message = format!("Unknown filter `{}`", name);
+                        let candidates: Vec<String> = BUILTIN_FILTERS.iter()
+                            .map(|&(builtin, _)| builtin.to_owned())
+                            .chain(self.options.filters.keys().cloned())
+                            .collect();
+                        if let Some(suggestion) = suggest(name, &candidates) {
+                            message.push_str(&format!("; did you mean `{}`?", suggestion));
+                        }
+                        return Err(self.error(template::Error::new(message, filter.offset)));
+                    }
+                },
+            };
+
+            steps.extend(quote! {
+                let __render_value: String = #step;
+            });
+        }
+
+        Ok((length, quote! {{ #steps __render_value }}))
+    }
+
+    /// Resolves `this` and `@index` against the innermost scope and anything else against the
+    /// struct fields. The type comes along when the path names a field or binding directly.
+    fn generate_path(&self, path: &Path) -> syn::Result<Value> {
+        let mut value = if path.is_index() {
+            let index = self.scopes.iter().rev()
+                .find_map(|scope| scope.index.as_ref())
+                .ok_or_else(|| self.error(template::Error::new(
+                    "`@index` used outside of an #each block".to_owned(),
+                    path.offset,
+                )))?;
+            Value { tokens: quote! { #index }, ty: Some(syn::parse_quote!(usize)), span: self.span }
+        } else if path.is_this() {
+            match self.scopes.last() {
+                Some(scope) => {
+                    let this = &scope.this;
+                    Value { tokens: quote! { #this }, ty: scope.ty.clone(), span: self.span }
+                }
+                // A bare `this` at the top level is the value itself, whose `Display` is this very
+                // template, so rendering it would recurse until the stack overflows
+                None if path.segments.len() == 1 => {
+                    return Err(self.error(template::Error::new(
+                        "`this` used outside of an #each or #with block; name a field instead".to_owned(),
+                        path.offset,
+                    )));
+                }
+                None => Value { tokens: quote! { self }, ty: None, span: self.span },
+            }
+        } else {
+            let binding = self.fields.get(&path.segments[0]).ok_or_else(|| {
+                let mut message = format!("Could not locate field `{}`", path.segments[0]);
+                if let Some(suggestion) = suggest(&path.segments[0], self.fields.keys()) {
+                    message.push_str(&format!("; did you mean `{}`?", suggestion));
+                }
+                self.error(template::Error::new(message, path.offset))
+            })?;
+            let field = binding.field;
+            Value { tokens: binding.tokens.clone(), ty: Some(field.ty.clone()), span: field.span() }
+        };
+
+        for segment in &path.segments[1..] {
+            let tokens = &value.tokens;
+            value.tokens = match segment.parse::<usize>() {
+                Ok(index) => {
+                    let index = syn::Index::from(index);
+                    quote! { #tokens.#index }
+                }
+                Err(_) => {
+                    let segment = syn::parse_str::<syn::Ident>(segment).map_err(|_| {
+                        self.error(template::Error::new(format!("Invalid field path `{}`", path), path.offset))
+                    })?;
+                    quote! { #tokens.#segment }
+                }
+            };
+            value.ty = None;
+        }
+
+        Ok(value)
+    }
+}
+
+fn generate_length(value: &Value) -> TokenStream {
+    let tokens = &value.tokens;
+    match &value.ty {
+        Some(ty) if is_string_like(ty) => quote! { #tokens.len() },
+        Some(ty) => {
+            let estimate = type_name(ty)
+                .and_then(|name| primitive_length(&name))
+                .unwrap_or(DEFAULT_LENGTH_ESTIMATE);
+            quote! { #estimate }
+        }
+        None => quote! { #DEFAULT_LENGTH_ESTIMATE },
+    }
+}
+
+/// The widest `Display` output of the primitive types, so their capacity never falls short.
+fn primitive_length(name: &str) -> Option<usize> {
+    let length = match name {
+        "bool" => 5,
+        "char" => 4,
+        "u8" => 3,
+        "i8" => 4,
+        "u16" => 5,
+        "i16" => 6,
+        "u32" => 10,
+        "i32" => 11,
+        "u64" | "i64" | "usize" | "isize" => 20,
+        "u128" => 39,
+        "i128" => 40,
+        _ => return None,
+    };
+    Some(length)
+}
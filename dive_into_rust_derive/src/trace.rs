@@ -0,0 +1,141 @@
+//! Code generation for the `#[trace]` attribute. The generated code names `Trace` unqualified,
+//! so it must be in scope where the attribute is used.
+
+use proc_macro2::TokenStream;
+use std::collections::HashSet;
+use syn::parse::Parser;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+/// Settings from `#[trace(...)]`.
+#[derive(Default)]
+struct Options {
+    /// `skip(a, b)`: arguments left out of the entry event, e.g. because they aren't `Debug`.
+    skip: Vec<syn::Ident>,
+    /// `skip_return`: don't log the return value.
+    skip_return: bool,
+}
+
+fn parse_options(args: TokenStream) -> syn::Result<Options> {
+    let mut options = Options::default();
+
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("skip") {
+            meta.parse_nested_meta(|argument| {
+                match argument.path.get_ident() {
+                    Some(ident) => {
+                        options.skip.push(ident.clone());
+                        Ok(())
+                    }
+                    None => Err(argument.error("Expected an argument name")),
+                }
+            })
+        } else if meta.path.is_ident("skip_return") {
+            options.skip_return = true;
+            Ok(())
+        } else {
+            Err(meta.error("Unknown trace option, expected `skip(...)` or `skip_return`"))
+        }
+    });
+    parser.parse2(args)?;
+
+    Ok(options)
+}
+
+/// Finds elided lifetimes, which can't be written in the closure's return type.
+#[derive(Default)]
+struct Elided(bool);
+
+impl<'ast> Visit<'ast> for Elided {
+    fn visit_type_reference(&mut self, reference: &'ast syn::TypeReference) {
+        self.0 |= reference.lifetime.is_none();
+        syn::visit::visit_type_reference(self, reference);
+    }
+
+    fn visit_lifetime(&mut self, lifetime: &'ast syn::Lifetime) {
+        self.0 |= lifetime.ident == "_";
+    }
+
+    fn visit_type_impl_trait(&mut self, _: &'ast syn::TypeImplTrait) {
+        self.0 = true;
+    }
+}
+
+/// Wraps the body in a closure that is called once, so the return value can be logged however
+/// the body exits, including `return` and `?`. The closure is called through `__trace_call_once`
+/// so it is an `FnOnce`, which can hand out borrows of the `&mut` arguments it captured.
+pub fn expand(args: TokenStream, function: &syn::ItemFn) -> syn::Result<TokenStream> {
+    let options = parse_options(args)?;
+    let sig = &function.sig;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new(asyncness.span, "trace doesn't support async functions"));
+    }
+    if let Some(constness) = &sig.constness {
+        return Err(syn::Error::new(constness.span, "trace doesn't support const functions"));
+    }
+
+    // Receivers and destructured arguments aren't logged
+    let mut names = HashSet::new();
+    let mut arguments = Vec::new();
+    for input in &sig.inputs {
+        if let syn::FnArg::Typed(typed) = input {
+            if let syn::Pat::Ident(pat) = &*typed.pat {
+                let ident = &pat.ident;
+                names.insert(ident);
+                if !options.skip.contains(ident) {
+                    let name = ident.to_string();
+                    arguments.push(quote_spanned! {typed.ty.span()=> (#name, &#ident as &dyn ::std::fmt::Debug) });
+                }
+            }
+        }
+    }
+    if let Some(unknown) = options.skip.iter().find(|ident| !names.contains(ident)) {
+        return Err(syn::Error::new(unknown.span(), format!("Could not locate argument `{}` to skip", unknown)));
+    }
+
+    let block = &function.block;
+
+    let return_type = match &sig.output {
+        syn::ReturnType::Type(_, ty) => ty,
+        // Functions returning `()` have nothing to log
+        syn::ReturnType::Default => {
+            return Ok(wrap(function, arguments, quote! {
+                __trace_call_once(move || #block);
+                __trace_guard.exit(None);
+            }));
+        }
+    };
+    let mut elided = Elided::default();
+    elided.visit_type(return_type);
+    let annotation = if elided.0 { quote! {} } else { quote! { -> #return_type } };
+    let result = if options.skip_return {
+        quote! { None }
+    } else {
+        quote_spanned! {return_type.span()=> Some(&__trace_result as &dyn ::std::fmt::Debug) }
+    };
+
+    Ok(wrap(function, arguments, quote! {
+        let __trace_result = __trace_call_once(move || #annotation #block);
+        __trace_guard.exit(#result);
+        __trace_result
+    }))
+}
+
+/// The function with `body` in place of its block, after entering the trace.
+fn wrap(function: &syn::ItemFn, arguments: Vec<TokenStream>, body: TokenStream) -> TokenStream {
+    let attrs = &function.attrs;
+    let vis = &function.vis;
+    let sig = &function.sig;
+    let name = sig.ident.to_string();
+    quote! {
+        #( #attrs )*
+        #vis #sig {
+            fn __trace_call_once<__R>(f: impl ::std::ops::FnOnce() -> __R) -> __R {
+                f()
+            }
+
+            let __trace_guard = Trace::enter(#name, &[ #( #arguments ),* ]);
+            #body
+        }
+    }
+}
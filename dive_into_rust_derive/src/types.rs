@@ -0,0 +1,88 @@
+//! Syntactic inspection of field types. The derive only sees tokens, so these helpers recognise
+//! types by the last segment of their path and give up on anything else.
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::HashSet;
+
+pub fn peel_references(mut ty: &syn::Type) -> &syn::Type {
+    while let syn::Type::Reference(reference) = ty {
+        ty = &reference.elem;
+    }
+    ty
+}
+
+/// The last path segment of a type, e.g. `Vec` for `std::vec::Vec<T>`.
+pub fn last_segment(ty: &syn::Type) -> Option<&syn::PathSegment> {
+    match peel_references(ty) {
+        syn::Type::Path(type_path) => type_path.path.segments.last(),
+        _ => None,
+    }
+}
+
+pub fn type_name(ty: &syn::Type) -> Option<String> {
+    last_segment(ty).map(|segment| segment.ident.to_string())
+}
+
+/// The first type argument, e.g. `T` for `Vec<T>` or `str` for `Cow<'a, str>`.
+pub fn type_argument(segment: &syn::PathSegment) -> Option<&syn::Type> {
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(arguments) => arguments.args.iter()
+            .find_map(|argument| match argument {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            }),
+        _ => None,
+    }
+}
+
+/// `String`, `str` and smart pointers to them render by copying their bytes.
+pub fn is_string_like(ty: &syn::Type) -> bool {
+    match last_segment(ty) {
+        Some(segment) if segment.ident == "String" || segment.ident == "str" => true,
+        Some(segment) if ["Cow", "Box", "Rc", "Arc"].iter().any(|name| segment.ident == name) => {
+            type_argument(segment).is_some_and(is_string_like)
+        }
+        _ => false,
+    }
+}
+
+/// The item type when iterating over a reference to `ty`.
+pub fn element_type(ty: &syn::Type) -> Option<&syn::Type> {
+    match peel_references(ty) {
+        syn::Type::Slice(slice) => Some(&slice.elem),
+        syn::Type::Array(array) => Some(&array.elem),
+        syn::Type::Path(_) => {
+            let segment = last_segment(ty)?;
+            let collections = ["Vec", "VecDeque", "LinkedList", "HashSet", "BTreeSet", "BinaryHeap"];
+            if collections.iter().any(|name| segment.ident == name) {
+                type_argument(segment)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+pub fn option_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let segment = last_segment(ty)?;
+    if segment.ident == "Option" {
+        type_argument(segment)
+    } else {
+        None
+    }
+}
+
+/// Whether any of `names`, such as the generic parameters of the derived type, appears in `ty`.
+pub fn mentions_any(ty: &syn::Type, names: &HashSet<String>) -> bool {
+    fn walk(tokens: TokenStream, names: &HashSet<String>) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Ident(ident) => names.contains(&ident.to_string()),
+            TokenTree::Group(group) => walk(group.stream(), names),
+            _ => false,
+        })
+    }
+
+    !names.is_empty() && walk(ty.to_token_stream(), names)
+}
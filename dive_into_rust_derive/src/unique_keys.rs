@@ -0,0 +1,72 @@
+//! Code generation for `unique_keys!`, the duplicate check behind the collection literal macros.
+
+use proc_macro2::TokenStream;
+use std::collections::HashMap;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+
+/// A canonical form of `expr` if it is a literal, or a tuple or array of literals, so that
+/// `0x10` and `16u8` count as the same key. Other expressions can't be compared at compile time.
+fn literal_key(expr: &syn::Expr) -> Option<String> {
+    match expr {
+        // Keys passed through `macro_rules!` arrive wrapped in an invisible group
+        syn::Expr::Group(group) => literal_key(&group.expr),
+        syn::Expr::Paren(paren) => literal_key(&paren.expr),
+        syn::Expr::Reference(reference) => literal_key(&reference.expr).map(|key| format!("&{}", key)),
+        syn::Expr::Lit(lit) => match &lit.lit {
+            syn::Lit::Str(s) => Some(format!("{:?}", s.value())),
+            syn::Lit::ByteStr(s) => Some(format!("b{:?}", s.value())),
+            syn::Lit::Byte(b) => Some(format!("b{:?}", b.value())),
+            syn::Lit::Char(c) => Some(format!("{:?}", c.value())),
+            syn::Lit::Int(i) => Some(i.base10_digits().to_owned()),
+            syn::Lit::Float(f) => Some(format!("{}f", f.base10_digits())),
+            syn::Lit::Bool(b) => Some(b.value.to_string()),
+            _ => None,
+        },
+        syn::Expr::Unary(syn::ExprUnary { op: syn::UnOp::Neg(_), expr, .. }) => {
+            literal_key(expr).map(|key| format!("-{}", key))
+        }
+        syn::Expr::Tuple(tuple) => {
+            let keys: Option<Vec<String>> = tuple.elems.iter().map(literal_key).collect();
+            keys.map(|keys| format!("({},)", keys.join(", ")))
+        }
+        syn::Expr::Array(array) => {
+            let keys: Option<Vec<String>> = array.elems.iter().map(literal_key).collect();
+            keys.map(|keys| format!("[{}]", keys.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+/// Expands to nothing, or to a compile error on every literal key that was already given.
+pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let keys = Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated.parse2(input)?;
+
+    let mut seen = HashMap::new();
+    let mut errors: Option<syn::Error> = None;
+    for (i, mut key) in keys.iter().enumerate() {
+        // Point at the key itself rather than at the invisible group from `macro_rules!`
+        while let syn::Expr::Group(group) = key {
+            key = &group.expr;
+        }
+        if let Some(literal) = literal_key(key) {
+            if let Some(&first) = seen.get(&literal) {
+                let error = syn::Error::new_spanned(
+                    key,
+                    format!("Duplicate key `{}`, already given as entry {}", quote!(#key), first + 1),
+                );
+                match &mut errors {
+                    Some(errors) => errors.combine(error),
+                    None => errors = Some(error),
+                }
+            } else {
+                seen.insert(literal, i);
+            }
+        }
+    }
+
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(TokenStream::new()),
+    }
+}
@@ -0,0 +1,8 @@
+//! The diagnostics the macros report at compile time, checked against the `.stderr` snapshots
+//! next to each case in `tests/ui`. Run with `TRYBUILD=overwrite` to update them.
+
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
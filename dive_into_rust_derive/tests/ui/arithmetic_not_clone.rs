@@ -0,0 +1,35 @@
+use dive_into_rust_derive::Arithmetic;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+// Every operator the derive needs, but no `Clone` for the by-reference impls
+struct Meters(f64);
+
+impl Add for Meters {
+    type Output = Meters;
+    fn add(self, other: Meters) -> Meters { Meters(self.0 + other.0) }
+}
+
+impl Sub for Meters {
+    type Output = Meters;
+    fn sub(self, other: Meters) -> Meters { Meters(self.0 - other.0) }
+}
+
+impl Neg for Meters {
+    type Output = Meters;
+    fn neg(self) -> Meters { Meters(-self.0) }
+}
+
+impl AddAssign for Meters {
+    fn add_assign(&mut self, other: Meters) { self.0 += other.0 }
+}
+
+impl SubAssign for Meters {
+    fn sub_assign(&mut self, other: Meters) { self.0 -= other.0 }
+}
+
+#[derive(Arithmetic)]
+struct Distance {
+    value: Meters,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use dive_into_rust_derive::Display;
+
+#[derive(Display)]
+#[display("{name:>widt$}")]
+struct Label {
+    name: String,
+    width: usize,
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+use dive_into_rust_derive::memoize;
+
+#[memoize]
+fn len(s: &str) -> usize {
+    s.len()
+}
+
+#[memoize]
+fn first(words: Vec<&str>) -> String {
+    words[0].to_owned()
+}
+
+#[memoize]
+fn sum(numbers: impl Iterator<Item = u32>) -> u32 {
+    numbers.sum()
+}
+
+// `'static` borrows are fine as keys
+#[memoize]
+fn greet(name: &'static str) -> String {
+    format!("Hello {}", name)
+}
+
+fn main() {
+    greet("world");
+}
@@ -0,0 +1,9 @@
+use dive_into_rust_derive::OrderBy;
+
+#[derive(OrderBy)]
+struct Series {
+    name: String,
+    samples: Vec<f64>,
+}
+
+fn main() {}
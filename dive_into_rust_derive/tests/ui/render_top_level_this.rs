@@ -0,0 +1,10 @@
+use dive_into_rust_derive::Render;
+
+// `this` here would be `Point` itself, rendered through its own template
+#[derive(Render)]
+#[Template = "x={{this}}"]
+struct Point {
+    x: i32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use dive_into_rust_derive::Render;
+
+#[derive(Render)]
+#[Template = "{{#each items}}\n  <li>{{this}}</li>\n"]
+struct List {
+    items: Vec<String>,
+}
+
+fn main() {}
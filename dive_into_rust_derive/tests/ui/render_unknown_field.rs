@@ -0,0 +1,9 @@
+use dive_into_rust_derive::Render;
+
+#[derive(Render)]
+#[Template = "Dear {{frist_name}},"]
+struct Letter {
+    first_name: String,
+}
+
+fn main() {}
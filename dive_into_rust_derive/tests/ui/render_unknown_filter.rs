@@ -0,0 +1,9 @@
+use dive_into_rust_derive::Render;
+
+#[derive(Render)]
+#[Template = "Hello {{name | uper}}!"]
+struct Greeting {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::escape::Escape;
+use crate::syntax::{self, split_arguments, Filter, Loader, Node, Path, Source, BUILTIN_FILTERS};
+use crate::value::{Context, Value};
+
+type FilterFn = Box<dyn Fn(&str, &[String]) -> String>;
+
+/// A template parsed at runtime, with its partials loaded. Unlike `#[derive(Render)]`, fields
+/// are looked up while rendering, so a misspelt name is only reported by `render`.
+pub struct Template {
+    nodes: Vec<Node>,
+    /// The template followed by every partial it includes.
+    sources: Vec<Source>,
+    escape: Escape,
+    filters: HashMap<String, FilterFn>,
+}
+
+impl Template {
+    /// Parses a template given as a string. Partials are resolved against the working directory.
+    pub fn parse(template: &str) -> Result<Template, Error> {
+        let source = Source {
+            contents: template.to_owned(),
+            location: "inline template".to_owned(),
+            path: None,
+        };
+        Template::load(source)
+    }
+
+    /// Reads and parses a template file. Partials are resolved against the including file.
+    pub fn from_file<P: AsRef<::std::path::Path>>(path: P) -> Result<Template, Error> {
+        let path = path.as_ref();
+        let location = path.to_string_lossy().into_owned();
+        let contents = fs::read_to_string(path).map_err(|err| Error {
+            message: format!("Couldn't read the file {}: {}", path.display(), err),
+            location: location.clone(),
+            position: None,
+        })?;
+        Template::load(Source { contents, location, path: Some(path.to_owned()) })
+    }
+
+    fn load(source: Source) -> Result<Template, Error> {
+        let mut loader = Loader::new(source, PathBuf::new());
+        let loaded = loader.load();
+        let mut template = Template {
+            nodes: Vec::new(),
+            sources: loader.sources,
+            escape: Escape::None,
+            filters: HashMap::new(),
+        };
+        template.nodes = loaded.map_err(|(source, err)| template.error_in(source, err))?;
+        Ok(template)
+    }
+
+    /// Escapes interpolated values, like `#[TemplateEscape = "..."]`.
+    pub fn escape(mut self, escape: Escape) -> Template {
+        self.escape = escape;
+        self
+    }
+
+    /// Registers a filter, like `#[TemplateFilter(name = function)]`. The function receives the
+    /// rendered value and the arguments written in the template, unquoted.
+    ///
+    /// # Panics
+    ///
+    /// If `name` is one of the built-in filters.
+    pub fn filter<F>(mut self, name: &str, filter: F) -> Template
+        where F: Fn(&str, &[String]) -> String + 'static
+    {
+        assert!(
+            !BUILTIN_FILTERS.iter().any(|&(builtin, _)| builtin == name),
+            "`{}` is a built-in filter",
+            name
+        );
+        self.filters.insert(name.to_owned(), Box::new(filter));
+        self
+    }
+
+    /// Renders the template with bare names looked up in `context`.
+    pub fn render(&self, context: &dyn Context) -> Result<String, Error> {
+        let mut renderer = Renderer {
+            template: self,
+            root: Value::Object(context),
+            current: 0,
+            scopes: Vec::new(),
+            output: String::new(),
+        };
+        renderer.render_nodes(&self.nodes)?;
+        Ok(renderer.output)
+    }
+
+    fn error_in(&self, source: usize, err: syntax::Error) -> Error {
+        let source = &self.sources[source];
+        Error {
+            message: err.message,
+            location: source.location.clone(),
+            position: Some(syntax::line_column(&source.contents, err.offset)),
+        }
+    }
+}
+
+/// A template that couldn't be loaded or rendered.
+#[derive(Debug)]
+pub struct Error {
+    pub message: String,
+    /// The file, partial or `inline template` the mistake is in.
+    pub location: String,
+    /// One-based line and column, unless the file couldn't be read at all.
+    pub position: Option<(usize, usize)>,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => write!(f, "{}:{}:{}: {}", self.location, line, column, self.message),
+            None => write!(f, "{}: {}", self.location, self.message),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// The value bound by an enclosing `{{#each}}` or `{{#with}}` block.
+struct Scope<'c> {
+    this: Value<'c>,
+    index: Option<usize>,
+}
+
+struct Renderer<'t, 'c> {
+    template: &'t Template,
+    root: Value<'c>,
+    /// The source whose nodes are being rendered, for error messages.
+    current: usize,
+    scopes: Vec<Scope<'c>>,
+    output: String,
+}
+
+impl<'t, 'c> Renderer<'t, 'c> {
+    fn error(&self, message: String, offset: usize) -> Error {
+        self.template.error_in(self.current, syntax::Error::new(message, offset))
+    }
+
+    fn render_nodes(&mut self, nodes: &[Node]) -> Result<(), Error> {
+        for node in nodes {
+            match node {
+                Node::Partial(partial) => {
+                    // Partials share the scope of the place they are included from
+                    let including = ::std::mem::replace(&mut self.current, partial.source);
+                    let rendered = self.render_nodes(&partial.nodes);
+                    self.current = including;
+                    rendered?;
+                }
+                Node::Text(text) => self.output.push_str(text),
+                Node::Field(path, filters) | Node::Raw(path, filters) => {
+                    let value = self.resolve(path)?;
+                    let mut text = match value {
+                        Value::Null => String::new(),
+                        Value::Bool(value) => value.to_string(),
+                        Value::Text(text) => text.into_owned(),
+                        Value::List(_) | Value::Object(_) => {
+                            return Err(self.error(format!("`{}` can't be interpolated", path), path.offset));
+                        }
+                    };
+                    for filter in filters {
+                        text = self.apply_filter(filter, text)?;
+                    }
+
+                    if let Node::Field(..) = node {
+                        self.template.escape.escape_into(&mut self.output, &text);
+                    } else {
+                        self.output.push_str(&text);
+                    }
+                }
+                Node::Each(path, body) => {
+                    let items = match self.resolve(path)? {
+                        Value::List(items) => items,
+                        Value::Null => Vec::new(),
+                        _ => return Err(self.error(format!("`{}` is not a list", path), path.offset)),
+                    };
+                    for (index, this) in items.into_iter().enumerate() {
+                        self.scopes.push(Scope { this, index: Some(index) });
+                        let rendered = self.render_nodes(body);
+                        self.scopes.pop();
+                        rendered?;
+                    }
+                }
+                Node::If(path, then, otherwise) => {
+                    let condition = match self.resolve(path)? {
+                        Value::Bool(condition) => condition,
+                        Value::Null => false,
+                        _ => return Err(self.error(format!("`{}` is not a bool", path), path.offset)),
+                    };
+                    self.render_nodes(if condition { then } else { otherwise })?;
+                }
+                Node::With(path, body, otherwise) => match self.resolve(path)? {
+                    Value::Null => self.render_nodes(otherwise)?,
+                    this => {
+                        self.scopes.push(Scope { this, index: None });
+                        let rendered = self.render_nodes(body);
+                        self.scopes.pop();
+                        rendered?;
+                    }
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolves `this` and `@index` against the innermost scope and anything else against the
+    /// context, then follows the remaining segments through objects and lists.
+    fn resolve(&self, path: &Path) -> Result<Value<'c>, Error> {
+        let mut value = if path.is_index() {
+            let index = self.scopes.iter().rev()
+                .find_map(|scope| scope.index)
+                .ok_or_else(|| self.error("`@index` used outside of an #each block".to_owned(), path.offset))?;
+            return Ok(Value::Text(index.to_string().into()));
+        } else if path.is_this() {
+            self.scopes.last().map_or_else(|| self.root.clone(), |scope| scope.this.clone())
+        } else {
+            self.field(&self.root, &path.segments[0])
+                .ok_or_else(|| self.error(format!("Could not locate field `{}`", path.segments[0]), path.offset))?
+        };
+
+        for segment in &path.segments[1..] {
+            value = self.field(&value, segment)
+                .ok_or_else(|| self.error(format!("Could not locate `{}` in `{}`", segment, path), path.offset))?;
+        }
+
+        Ok(value)
+    }
+
+    fn field(&self, value: &Value<'c>, name: &str) -> Option<Value<'c>> {
+        match value {
+            Value::Object(object) => object.field(name),
+            Value::List(items) => name.parse::<usize>().ok().and_then(|index| items.get(index).cloned()),
+            _ => None,
+        }
+    }
+
+    fn apply_filter(&self, filter: &Filter, value: String) -> Result<String, Error> {
+        let arguments = split_arguments(&filter.arguments);
+        let wrong_arguments = || {
+            let usage = BUILTIN_FILTERS.iter().find(|&&(builtin, _)| builtin == filter.name).unwrap().1;
+            self.error(
+                format!("Wrong arguments for filter `{}`, expected `{}`", filter.name, usage),
+                filter.offset,
+            )
+        };
+
+        match (filter.name.as_str(), arguments.as_slice()) {
+            ("upper", []) => Ok(value.to_uppercase()),
+            ("lower", []) => Ok(value.to_lowercase()),
+            ("trim", []) => Ok(value.trim().to_owned()),
+            ("truncate", [count]) => {
+                let count: usize = count.parse().map_err(|_| wrong_arguments())?;
+                Ok(value.chars().take(count).collect())
+            }
+            ("pad_left", [width]) | ("pad_left", [width, _]) => {
+                let width: usize = width.parse().map_err(|_| wrong_arguments())?;
+                let fill = match arguments.get(1) {
+                    Some(fill) if fill.chars().count() == 1 => fill.chars().next().unwrap(),
+                    Some(_) => return Err(wrong_arguments()),
+                    None => ' ',
+                };
+                let mut padded = String::with_capacity(width);
+                for _ in value.chars().count()..width {
+                    padded.push(fill);
+                }
+                Ok(padded + &value)
+            }
+            (name, _) if BUILTIN_FILTERS.iter().any(|&(builtin, _)| builtin == name) => Err(wrong_arguments()),
+            (name, arguments) => match self.template.filters.get(name) {
+                Some(function) => Ok(function(&value, arguments)),
+                None => Err(self.error(format!("Unknown filter `{}`", name), filter.offset)),
+            },
+        }
+    }
+}
@@ -0,0 +1,39 @@
+/// How interpolated values are escaped. `{{{raw}}}` tags are never escaped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Escape {
+    None,
+    Html,
+    Xml,
+}
+
+impl Escape {
+    /// Looks up `"html"`, `"xml"` or `"none"`, as written in `#[TemplateEscape = "..."]`.
+    pub fn from_name(name: &str) -> Option<Escape> {
+        match name {
+            "html" => Some(Escape::Html),
+            "xml" => Some(Escape::Xml),
+            "none" => Some(Escape::None),
+            _ => None,
+        }
+    }
+
+    /// The characters that are replaced and their replacements.
+    pub fn entities(self) -> &'static [(char, &'static str)] {
+        match self {
+            Escape::None => &[],
+            Escape::Html => &[('&', "&amp;"), ('<', "&lt;"), ('>', "&gt;"), ('"', "&quot;"), ('\'', "&#x27;")],
+            Escape::Xml => &[('&', "&amp;"), ('<', "&lt;"), ('>', "&gt;"), ('"', "&quot;"), ('\'', "&apos;")],
+        }
+    }
+
+    /// Appends `s` to `output`, replacing the characters this escaping covers.
+    pub fn escape_into(self, output: &mut String, s: &str) {
+        let entities = self.entities();
+        for c in s.chars() {
+            match entities.iter().find(|&&(special, _)| special == c) {
+                Some(&(_, entity)) => output.push_str(entity),
+                None => output.push(c),
+            }
+        }
+    }
+}
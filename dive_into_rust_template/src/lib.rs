@@ -0,0 +1,69 @@
+//! The template language of `#[derive(Render)]`, usable at runtime.
+//!
+//! The derive checks a template against the fields of a struct while compiling. `Template`
+//! parses the same syntax at runtime instead and renders it against anything implementing
+//! `Context`, such as a `HashMap<&str, Value>`:
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use dive_into_rust_template::{Template, ToValue};
+//!
+//! let template = Template::parse("Hello {{name | upper}}!").unwrap();
+//! let mut context = HashMap::new();
+//! context.insert("name", "world".to_value());
+//! assert_eq!(template.render(&context).unwrap(), "Hello WORLD!");
+//! ```
+
+use std::fmt;
+use std::io;
+
+mod engine;
+mod escape;
+pub mod syntax;
+mod value;
+
+pub use engine::{Error, Template};
+pub use escape::Escape;
+pub use value::{Context, ToValue, Value};
+
+/// A type that renders itself through a template, usually implemented with `#[derive(Render)]`,
+/// which also implements `Display` on top of `render_to`.
+pub trait Render {
+    /// Writes the rendered template into `writer` piece by piece, without building a `String`.
+    fn render_to<W: fmt::Write + ?Sized>(&self, writer: &mut W) -> fmt::Result;
+
+    /// An estimate of the rendered length, used to size the buffer in `render`.
+    fn render_length(&self) -> usize {
+        0
+    }
+
+    fn render(&self) -> String {
+        let mut output = String::with_capacity(self.render_length());
+        self.render_to(&mut output).expect("writing to a String can't fail");
+        output
+    }
+
+    /// Streams the rendered template into a file, socket or any other `io::Write`.
+    fn write_to<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        let mut adapter = IoWriter { writer, error: None };
+        match self.render_to(&mut adapter) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(adapter.error.unwrap_or_else(|| io::Error::other("formatting a value failed"))),
+        }
+    }
+}
+
+/// Lets `render_to` write into an `io::Write`, keeping the I/O error that `fmt::Error` can't carry.
+struct IoWriter<W> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.writer.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
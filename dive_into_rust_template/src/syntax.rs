@@ -0,0 +1,410 @@
+//! The template syntax shared by `#[derive(Render)]` and the runtime `Template`.
+
+use std::fs;
+use std::path::PathBuf;
+
+/// A parsed piece of a `Render` template.
+#[derive(Debug)]
+pub enum Node {
+    Text(String),
+    Field(Path, Vec<Filter>),
+    /// `{{{path}}}`, interpolated without escaping.
+    Raw(Path, Vec<Filter>),
+    Each(Path, Vec<Node>),
+    If(Path, Vec<Node>, Vec<Node>),
+    With(Path, Vec<Node>, Vec<Node>),
+    Partial(Partial),
+}
+
+/// `{{> header.tpl}}`. The nodes are filled in once the included file has been loaded.
+#[derive(Debug)]
+pub struct Partial {
+    pub name: String,
+    pub offset: usize,
+    /// Index of the included file among the loaded template sources.
+    pub source: usize,
+    pub nodes: Vec<Node>,
+}
+
+/// A dotted reference inside a tag, such as `name`, `this.name` or `@index`.
+#[derive(Debug)]
+pub struct Path {
+    pub segments: Vec<String>,
+    /// Byte offset of the tag in the template, for error messages.
+    pub offset: usize,
+}
+
+impl Path {
+    fn parse(source: &str, offset: usize) -> Result<Path, Error> {
+        let segments: Vec<String> = source.split('.').map(|s| s.trim().to_owned()).collect();
+        if segments.iter().any(|s| s.is_empty()) {
+            return Err(Error::new(format!("Invalid field path `{}`", source), offset));
+        }
+        Ok(Path { segments, offset })
+    }
+
+    pub fn is_this(&self) -> bool {
+        self.segments[0] == "this"
+    }
+
+    pub fn is_index(&self) -> bool {
+        self.segments.len() == 1 && self.segments[0] == "@index"
+    }
+}
+
+impl ::std::fmt::Display for Path {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(f, "{}", self.segments.join("."))
+    }
+}
+
+/// A `| name(arguments)` step applied to an interpolated value.
+#[derive(Debug)]
+pub struct Filter {
+    pub name: String,
+    /// The text between the parentheses, parsed as Rust expressions later on.
+    pub arguments: String,
+    pub offset: usize,
+}
+
+impl Filter {
+    fn parse(source: &str, offset: usize) -> Result<Filter, Error> {
+        let source = source.trim();
+        let (name, arguments) = match source.find('(') {
+            Some(open) if source.ends_with(')') => (&source[..open], &source[open + 1..source.len() - 1]),
+            Some(_) => return Err(Error::new(format!("Unclosed filter arguments `{}`", source), offset)),
+            None => (source, ""),
+        };
+
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(Error::new("Missing filter name after `|`".to_owned(), offset));
+        }
+        Ok(Filter { name: name.to_owned(), arguments: arguments.to_owned(), offset })
+    }
+}
+
+/// The filters every template can use, with their usage for error messages.
+pub const BUILTIN_FILTERS: &[(&str, &str)] = &[
+    ("upper", "upper"),
+    ("lower", "lower"),
+    ("trim", "trim"),
+    ("truncate", "truncate(length)"),
+    ("pad_left", "pad_left(width, fill)"),
+];
+
+/// Splits filter arguments such as `8, '0'` into their values, dropping the quotes of
+/// character and string literals.
+pub fn split_arguments(arguments: &str) -> Vec<String> {
+    if arguments.trim().is_empty() {
+        return Vec::new();
+    }
+
+    split_outside_quotes(arguments, ',')
+        .into_iter()
+        .map(|argument| {
+            let argument = argument.trim();
+            let quoted = argument.len() >= 2
+                && (argument.starts_with('\'') || argument.starts_with('"'))
+                && argument.ends_with(&argument[..1]);
+            if quoted {
+                argument[1..argument.len() - 1].replace("\\\\", "\\").replace("\\'", "'").replace("\\\"", "\"")
+            } else {
+                argument.to_owned()
+            }
+        })
+        .collect()
+}
+
+/// Parses `path | filter | filter(arguments)`.
+fn parse_expression(source: &str, offset: usize) -> Result<(Path, Vec<Filter>), Error> {
+    let mut parts = split_outside_quotes(source, '|').into_iter();
+    let path = Path::parse(parts.next().unwrap_or(""), offset)?;
+    let filters = parts.map(|part| Filter::parse(part, offset)).collect::<Result<_, _>>()?;
+    Ok((path, filters))
+}
+
+/// Splits on `separator`, except inside `'...'` and `"..."` literals.
+fn split_outside_quotes(source: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (i, c) in source.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => (),
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c == separator => {
+                parts.push(&source[start..i]);
+                start = i + c.len_utf8();
+            }
+            None => (),
+        }
+    }
+    parts.push(&source[start..]);
+
+    parts
+}
+
+/// A mistake in the template, located by its byte offset.
+#[derive(Debug)]
+pub struct Error {
+    pub message: String,
+    pub offset: usize,
+}
+
+impl Error {
+    pub fn new(message: String, offset: usize) -> Error {
+        Error { message, offset }
+    }
+}
+
+/// One-based line and column of a byte offset.
+pub fn line_column(template: &str, offset: usize) -> (usize, usize) {
+    let before = &template[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+    (line, column)
+}
+
+enum Tag<'a> {
+    Field(&'a str),
+    Else,
+    Partial(&'a str),
+    Open(&'a str, &'a str),
+    Close(&'a str),
+}
+
+impl<'a> Tag<'a> {
+    fn parse(content: &'a str) -> Tag<'a> {
+        let content = content.trim();
+        if let Some(block) = content.strip_prefix('#') {
+            let mut parts = block.splitn(2, char::is_whitespace);
+            let keyword = parts.next().unwrap_or("");
+            let argument = parts.next().unwrap_or("").trim();
+            Tag::Open(keyword, argument)
+        } else if let Some(block) = content.strip_prefix('/') {
+            Tag::Close(block.trim())
+        } else if let Some(name) = content.strip_prefix('>') {
+            Tag::Partial(name.trim())
+        } else if content == "else" {
+            Tag::Else
+        } else {
+            Tag::Field(content)
+        }
+    }
+}
+
+/// A block whose closing tag has not been reached yet.
+struct Block<'a> {
+    keyword: &'a str,
+    path: Path,
+    body: Vec<Node>,
+    otherwise: Option<Vec<Node>>,
+}
+
+impl<'a> Block<'a> {
+    fn nodes(&mut self) -> &mut Vec<Node> {
+        match self.otherwise {
+            Some(ref mut otherwise) => otherwise,
+            None => &mut self.body,
+        }
+    }
+
+    fn into_node(self) -> Node {
+        let otherwise = self.otherwise.unwrap_or_default();
+        match self.keyword {
+            "each" => Node::Each(self.path, self.body),
+            "if" => Node::If(self.path, self.body, otherwise),
+            _ => Node::With(self.path, self.body, otherwise),
+        }
+    }
+}
+
+/// Splits a template on `{{`/`}}` (`{{{`/`}}}` for raw values) and nests `{{#each}}`, `{{#if}}`
+/// and `{{#with}}` blocks. Partials are left for the caller to load.
+pub fn parse(template: &str) -> Result<Vec<Node>, Error> {
+    let mut stack: Vec<Block> = Vec::new();
+    let mut nodes = Vec::new();
+    let mut position = 0;
+
+    while let Some(open) = template[position..].find("{{") {
+        let offset = position + open;
+        let text = &template[position..offset];
+
+        let current = stack.last_mut().map(Block::nodes).unwrap_or(&mut nodes);
+        if !text.is_empty() {
+            current.push(Node::Text(text.to_owned()));
+        }
+
+        if template[offset + 2..].starts_with('{') {
+            let close = template[offset + 3..]
+                .find("}}}")
+                .ok_or_else(|| Error::new("Unclosed tag, expected `}}}`".to_owned(), offset))?;
+            let (path, filters) = parse_expression(&template[offset + 3..offset + 3 + close], offset)?;
+            current.push(Node::Raw(path, filters));
+            position = offset + 3 + close + 3;
+            continue;
+        }
+
+        let close = template[offset + 2..]
+            .find("}}")
+            .ok_or_else(|| Error::new("Unclosed tag, expected `}}`".to_owned(), offset))?;
+        let content = &template[offset + 2..offset + 2 + close];
+
+        match Tag::parse(content) {
+            Tag::Field(content) => {
+                let (path, filters) = parse_expression(content, offset)?;
+                current.push(Node::Field(path, filters));
+            }
+            Tag::Partial("") => return Err(Error::new("Missing partial name after `{{>`".to_owned(), offset)),
+            Tag::Partial(name) => current.push(Node::Partial(Partial {
+                name: name.to_owned(),
+                offset,
+                source: 0,
+                nodes: Vec::new(),
+            })),
+            Tag::Else => {
+                let block = stack
+                    .last_mut()
+                    .filter(|block| block.keyword != "each" && block.otherwise.is_none())
+                    .ok_or_else(|| Error::new("Unexpected `{{else}}` outside of #if or #with".to_owned(), offset))?;
+                block.otherwise = Some(Vec::new());
+            }
+            Tag::Open(keyword, argument) => match keyword {
+                "each" | "if" | "with" => stack.push(Block {
+                    keyword,
+                    path: Path::parse(argument, offset)?,
+                    body: Vec::new(),
+                    otherwise: None,
+                }),
+                _ => return Err(Error::new(format!("Unknown block `#{}`", keyword), offset)),
+            },
+            Tag::Close(keyword) => {
+                let block = stack
+                    .pop()
+                    .ok_or_else(|| Error::new(format!("Unexpected closing tag `/{}`", keyword), offset))?;
+                if block.keyword != keyword {
+                    return Err(Error::new(
+                        format!("Expected `/{}` but found `/{}`", block.keyword, keyword),
+                        offset,
+                    ));
+                }
+                let current = stack.last_mut().map(Block::nodes).unwrap_or(&mut nodes);
+                current.push(block.into_node());
+            }
+        }
+
+        position = offset + 2 + close + 2;
+    }
+
+    if let Some(block) = stack.last() {
+        return Err(Error::new(
+            format!("Unclosed block `#{} {}`", block.keyword, block.path),
+            block.path.offset,
+        ));
+    }
+    if position < template.len() {
+        nodes.push(Node::Text(template[position..].to_owned()));
+    }
+
+    Ok(nodes)
+}
+
+/// The text of a template or partial and where it comes from.
+#[derive(Debug)]
+pub struct Source {
+    pub contents: String,
+    /// The path as given by the user or written in `{{> ...}}`, or `inline template`, for error messages.
+    pub location: String,
+    /// The resolved file, for templates that aren't inline.
+    pub path: Option<PathBuf>,
+}
+
+/// Parses a template and, recursively, the partials it includes. Partials are resolved against
+/// the file including them, or against `base` when they are included from an inline template.
+pub struct Loader {
+    /// The template followed by every partial it includes, in the order they were loaded.
+    pub sources: Vec<Source>,
+    base: PathBuf,
+}
+
+impl Loader {
+    pub fn new(source: Source, base: PathBuf) -> Loader {
+        Loader { sources: vec![source], base }
+    }
+
+    /// Parses the template and loads its partials. A mistake comes with the index of the source
+    /// it is in, so the caller can report its location.
+    pub fn load(&mut self) -> Result<Vec<Node>, (usize, Error)> {
+        self.load_source(0, &mut Vec::new())
+    }
+
+    /// `including` holds the files on the current include chain, so a file including itself is
+    /// reported rather than looping.
+    fn load_source(&mut self, source: usize, including: &mut Vec<(PathBuf, String)>)
+                   -> Result<Vec<Node>, (usize, Error)> {
+        let mut nodes = parse(&self.sources[source].contents).map_err(|err| (source, err))?;
+
+        if let Some(path) = &self.sources[source].path {
+            let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+            including.push((canonical, self.sources[source].location.clone()));
+        }
+        let loaded = self.load_partials(&mut nodes, source, including);
+        if self.sources[source].path.is_some() {
+            including.pop();
+        }
+
+        loaded.map(|_| nodes)
+    }
+
+    fn load_partials(&mut self, nodes: &mut [Node], source: usize, including: &mut Vec<(PathBuf, String)>)
+                     -> Result<(), (usize, Error)> {
+        for node in nodes {
+            match node {
+                Node::Each(_, body) => self.load_partials(body, source, including)?,
+                Node::If(_, then, otherwise) | Node::With(_, then, otherwise) => {
+                    self.load_partials(then, source, including)?;
+                    self.load_partials(otherwise, source, including)?;
+                }
+                Node::Partial(partial) => {
+                    let (path, location) = match &self.sources[source].path {
+                        Some(parent) => {
+                            let directory = parent.parent().unwrap_or_else(|| ::std::path::Path::new(""));
+                            let location = ::std::path::Path::new(&self.sources[source].location)
+                                .with_file_name(&partial.name);
+                            (directory.join(&partial.name), location.to_string_lossy().into_owned())
+                        }
+                        None => (self.base.join(&partial.name), partial.name.clone()),
+                    };
+
+                    let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+                    if let Some(start) = including.iter().position(|(path, _)| *path == canonical) {
+                        let mut chain: Vec<&str> = including[start..].iter()
+                            .map(|(_, location)| location.as_str())
+                            .collect();
+                        chain.push(&location);
+                        let message = format!("Include cycle: {}", chain.join(" -> "));
+                        return Err((source, Error::new(message, partial.offset)));
+                    }
+
+                    let contents = fs::read_to_string(&path).map_err(|err| {
+                        let message = format!("Couldn't read the partial {}: {}", path.display(), err);
+                        (source, Error::new(message, partial.offset))
+                    })?;
+
+                    self.sources.push(Source { contents, location, path: Some(path) });
+                    partial.source = self.sources.len() - 1;
+                    partial.nodes = self.load_source(partial.source, including)?;
+                }
+                Node::Text(_) | Node::Field(..) | Node::Raw(..) => (),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use std::borrow::{Borrow, Cow};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+/// Something a runtime template can look at: text to interpolate, a condition for `{{#if}}`,
+/// a list for `{{#each}}` or an object whose fields are looked up by name.
+#[derive(Clone)]
+pub enum Value<'a> {
+    /// A missing value; renders as nothing and is false in `{{#if}}` and `{{#with}}`.
+    Null,
+    Bool(bool),
+    Text(Cow<'a, str>),
+    List(Vec<Value<'a>>),
+    Object(&'a dyn Context),
+}
+
+/// The fields a template can reach by name, the runtime counterpart of a struct's fields.
+pub trait Context {
+    fn field(&self, name: &str) -> Option<Value<'_>>;
+}
+
+/// Conversion into a template `Value`, borrowing where possible.
+pub trait ToValue {
+    fn to_value(&self) -> Value<'_>;
+}
+
+impl ToValue for Value<'_> {
+    fn to_value(&self) -> Value<'_> {
+        self.clone()
+    }
+}
+
+impl ToValue for str {
+    fn to_value(&self) -> Value<'_> {
+        Value::Text(Cow::Borrowed(self))
+    }
+}
+
+impl ToValue for String {
+    fn to_value(&self) -> Value<'_> {
+        Value::Text(Cow::Borrowed(self))
+    }
+}
+
+impl ToValue for bool {
+    fn to_value(&self) -> Value<'_> {
+        Value::Bool(*self)
+    }
+}
+
+macro_rules! to_value_display {
+    ($($ty:ty),*) => {
+        $(
+            impl ToValue for $ty {
+                fn to_value(&self) -> Value<'_> {
+                    Value::Text(Cow::Owned(self.to_string()))
+                }
+            }
+        )*
+    };
+}
+
+to_value_display!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, char);
+
+impl<T: ToValue + ?Sized> ToValue for &T {
+    fn to_value(&self) -> Value<'_> {
+        (**self).to_value()
+    }
+}
+
+impl<T: ToValue> ToValue for Option<T> {
+    fn to_value(&self) -> Value<'_> {
+        match self {
+            Some(value) => value.to_value(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl<T: ToValue> ToValue for [T] {
+    fn to_value(&self) -> Value<'_> {
+        Value::List(self.iter().map(ToValue::to_value).collect())
+    }
+}
+
+impl<T: ToValue> ToValue for Vec<T> {
+    fn to_value(&self) -> Value<'_> {
+        self.as_slice().to_value()
+    }
+}
+
+impl<K, V, S> Context for HashMap<K, V, S>
+    where K: Borrow<str> + Hash + Eq, V: ToValue, S: BuildHasher
+{
+    fn field(&self, name: &str) -> Option<Value<'_>> {
+        self.get(name).map(ToValue::to_value)
+    }
+}
+
+impl<K, V, S> ToValue for HashMap<K, V, S>
+    where K: Borrow<str> + Hash + Eq, V: ToValue, S: BuildHasher
+{
+    fn to_value(&self) -> Value<'_> {
+        Value::Object(self)
+    }
+}
+
+impl<K, V> Context for BTreeMap<K, V>
+    where K: Borrow<str> + Ord, V: ToValue
+{
+    fn field(&self, name: &str) -> Option<Value<'_>> {
+        self.get(name).map(ToValue::to_value)
+    }
+}
+
+impl<K, V> ToValue for BTreeMap<K, V>
+    where K: Borrow<str> + Ord, V: ToValue
+{
+    fn to_value(&self) -> Value<'_> {
+        Value::Object(self)
+    }
+}
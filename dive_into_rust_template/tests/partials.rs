@@ -0,0 +1,34 @@
+//! Loading partials, shared by `Template` and `#[derive(Render)]`. The derive reports the same
+//! errors at compile time, but its template paths are relative to the crate being compiled, which
+//! a compile-fail test can't point at these files.
+
+use dive_into_rust_template::syntax::{Loader, Source};
+use dive_into_rust_template::Template;
+
+const TEMPLATES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/templates");
+
+#[test]
+fn include_cycle() {
+    let path = format!("{}/cycle_a.tpl", TEMPLATES);
+    let err = Template::from_file(&path).err().expect("a cycle must not load");
+
+    let expected = format!("Include cycle: {0}/cycle_a.tpl -> {0}/cycle_b.tpl -> {0}/cycle_a.tpl", TEMPLATES);
+    assert_eq!(err.message, expected);
+    assert_eq!(err.location, format!("{}/cycle_b.tpl", TEMPLATES));
+    assert_eq!(err.position, Some((1, 8)));
+}
+
+#[test]
+fn inline_partials_use_the_base_directory() {
+    let source = Source {
+        contents: "{{> cycle_b.tpl}}".to_owned(),
+        location: "inline template".to_owned(),
+        path: None,
+    };
+    let (source, err) = Loader::new(source, TEMPLATES.into()).load().expect_err("a cycle must not load");
+
+    // The inline template isn't a file, so the chain starts at the first partial. The cycle is
+    // found in `cycle_a.tpl`, the third source after the template and `cycle_b.tpl`.
+    assert_eq!(source, 2);
+    assert_eq!(err.message, "Include cycle: cycle_b.tpl -> cycle_a.tpl -> cycle_b.tpl");
+}
@@ -0,0 +1 @@
+header {{> cycle_b.tpl}}
@@ -0,0 +1 @@
+nested {{> cycle_a.tpl}}